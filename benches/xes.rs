@@ -16,7 +16,7 @@ fn xes_read() {
 }
 
 fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("xes_read", |b| b.iter(|| xes_read()));
+    c.bench_function("xes_read", |b| b.iter(xes_read));
 }
 
 criterion_group!(benches, criterion_benchmark);
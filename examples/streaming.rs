@@ -81,7 +81,27 @@ fn example_2() {
     println!("{}", observer.release().unwrap());
 }
 
+/// Load a XES file, store it gzip compressed and render it as string
+///
+/// path > Log > path | Log > String
+///
+fn example_3() {
+    let path = expand_static(&["xes", "book", "L1.xes"]);
+    let log = Log::open(&path).unwrap();
+
+    let compressed = std::env::temp_dir().join("L1.xes.gz");
+    println!("store {:?} as {:?}", &path, &compressed);
+    log.save(&compressed).unwrap();
+
+    println!(
+        "{}",
+        Log::open(&compressed).unwrap().to_xes_string().unwrap()
+    );
+    fs::remove_file(&compressed).unwrap();
+}
+
 fn main() {
     example_1();
     example_2();
+    example_3();
 }
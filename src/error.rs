@@ -2,6 +2,7 @@
 //!
 
 // standard library
+use std::fmt;
 use std::fmt::Debug;
 
 // third party
@@ -10,6 +11,37 @@ use thiserror::Error;
 // local
 use crate::stream;
 
+/// Location of an error in the input
+///
+/// Lines and columns start at one, columns count bytes. The byte offset starts at zero. `trace` is
+/// the index of the enclosing trace, `event` the index of the enclosing event within its trace, or
+/// among the events outside of traces.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+    pub trace: Option<usize>,
+    pub event: Option<usize>,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)?;
+
+        if let Some(trace) = self.trace {
+            write!(f, ", trace {}", trace)?;
+        }
+
+        if let Some(event) = self.event {
+            write!(f, ", event {}", event)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// A common error type for promi
 #[derive(Error, Debug, Clone)]
 pub enum Error {
@@ -25,9 +57,15 @@ pub enum Error {
     #[error("Validation Error: {0}")]
     ValidationError(String),
 
+    #[error("Expression Error: {0}")]
+    ExpressionError(String),
+
     #[error("key error {0} not found")]
     KeyError(String),
 
+    #[error("unsupported format: {0}")]
+    FormatError(String),
+
     #[error("{0}")]
     XMLError(String),
 
@@ -51,6 +89,44 @@ pub enum Error {
 
     #[error("{0}")]
     ChannelError(String),
+
+    #[error("{0}")]
+    IOError(String),
+
+    #[error("{error} at {position}")]
+    PositionError {
+        position: Position,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Attach a position, unless the error already has one
+    pub fn at(self, position: Position) -> Self {
+        match self {
+            Error::PositionError { .. } => self,
+            error => Error::PositionError {
+                position,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Position of the error in the input, if known
+    pub fn position(&self) -> Option<&Position> {
+        match self {
+            Error::PositionError { position, .. } => Some(position),
+            _ => None,
+        }
+    }
+
+    /// The error without its position
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::PositionError { error, .. } => error.inner(),
+            error => error,
+        }
+    }
 }
 
 // Manual conversion as quick-xml errors don't support cloning
@@ -60,6 +136,13 @@ impl From<quick_xml::Error> for Error {
     }
 }
 
+// Manual conversion as io errors don't support cloning
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::IOError(format!("{:?}", error))
+    }
+}
+
 // Manual conversion as string errors errors don't support cloning
 impl From<std::string::FromUtf8Error> for Error {
     fn from(error: std::string::FromUtf8Error) -> Self {
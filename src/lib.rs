@@ -45,7 +45,14 @@ pub mod error;
 pub mod stream;
 
 use std::convert::TryFrom;
-use stream::{buffer, Element, StreamSink};
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use stream::xes::{XesReader, XesWriter};
+use stream::{buffer, Element, Meta, StreamSink};
 
 /// promi's datetime type
 pub type DateTime = chrono::DateTime<chrono::FixedOffset>;
@@ -65,25 +72,108 @@ pub enum AttributeType {
     List(Vec<Attribute>),
 }
 
+impl AttributeType {
+    /// Value of a string attribute
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            AttributeType::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Value of a date attribute
+    pub fn as_date(&self) -> Option<&DateTime> {
+        match self {
+            AttributeType::Date(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Value of an int attribute
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            AttributeType::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of a float attribute
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            AttributeType::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of a boolean attribute
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self {
+            AttributeType::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Value of an id attribute
+    pub fn as_id(&self) -> Option<&str> {
+        match self {
+            AttributeType::Id(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Items of a list attribute
+    pub fn as_list(&self) -> Option<&[Attribute]> {
+        match self {
+            AttributeType::List(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeType::String(value) | AttributeType::Id(value) => write!(f, "{}", value),
+            AttributeType::Date(value) => write!(
+                f,
+                "{}",
+                value.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+            ),
+            AttributeType::Int(value) => write!(f, "{}", value),
+            AttributeType::Float(value) => write!(f, "{}", value),
+            AttributeType::Boolean(value) => write!(f, "{}", value),
+            AttributeType::List(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value.value)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
 /// Represents whether global or classifier target events or traces
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Scope {
     Event,
     Trace,
 }
 
-impl TryFrom<Option<String>> for Scope {
+/// Scope assumed by globals and classifiers that don't declare one
+const DEFAULT_SCOPE: Scope = Scope::Event;
+
+impl TryFrom<&str> for Scope {
     type Error = crate::error::Error;
 
-    fn try_from(value: Option<String>) -> Result<Self, Self::Error> {
-        if let Some(s) = value {
-            match s.as_str() {
-                "trace" => Ok(Scope::Trace),
-                "event" => Ok(Scope::Event),
-                other => Err(Self::Error::XesError(format!("Invalid scope: {:?}", other))),
-            }
-        } else {
-            Ok(Scope::Event)
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "trace" => Ok(Scope::Trace),
+            "event" => Ok(Scope::Event),
+            other => Err(Self::Error::XesError(format!("Invalid scope: {:?}", other))),
         }
     }
 }
@@ -99,6 +189,115 @@ impl TryFrom<Option<String>> for Scope {
 pub struct Attribute {
     key: String,
     value: AttributeType,
+    attributes: Vec<Attribute>,
+    /// Keys of the `<values>` elements of a list as read from XES, checked by the `XesValidator`
+    values: Option<Vec<Option<String>>>,
+}
+
+impl Attribute {
+    /// Create a new attribute without nested attributes
+    pub fn new<K: Into<String>>(key: K, value: AttributeType) -> Self {
+        Attribute {
+            key: key.into(),
+            value,
+            attributes: Vec::new(),
+            values: None,
+        }
+    }
+
+    /// Add a nested attribute
+    pub fn with_attribute(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> &AttributeType {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut AttributeType {
+        &mut self.value
+    }
+
+    /// Replace the value, returning the previous one
+    pub fn set_value(&mut self, value: AttributeType) -> AttributeType {
+        std::mem::replace(&mut self.value, value)
+    }
+
+    /// Nested attributes
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    /// Look up a nested attribute by key
+    pub fn attribute(&self, key: &str) -> Option<&Attribute> {
+        find_attribute(&self.attributes, key)
+    }
+
+    pub fn attribute_mut(&mut self, key: &str) -> Option<&mut Attribute> {
+        self.attributes.iter_mut().find(|a| a.key == key)
+    }
+
+    /// Insert a nested attribute, replacing and returning one with the same key
+    pub fn set_attribute(&mut self, attribute: Attribute) -> Option<Attribute> {
+        set_attribute(&mut self.attributes, attribute)
+    }
+
+    /// Remove and return the nested attribute with the given key
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Attribute> {
+        remove_attribute(&mut self.attributes, key)
+    }
+}
+
+fn find_attribute<'a>(attributes: &'a [Attribute], key: &str) -> Option<&'a Attribute> {
+    attributes.iter().find(|a| a.key == key)
+}
+
+fn set_attribute(attributes: &mut Vec<Attribute>, attribute: Attribute) -> Option<Attribute> {
+    match attributes.iter_mut().find(|a| a.key == attribute.key) {
+        Some(existing) => Some(std::mem::replace(existing, attribute)),
+        None => {
+            attributes.push(attribute);
+            None
+        }
+    }
+}
+
+fn remove_attribute(attributes: &mut Vec<Attribute>, key: &str) -> Option<Attribute> {
+    let position = attributes.iter().position(|a| a.key == key)?;
+    Some(attributes.remove(position))
+}
+
+/// Log level declarations of an extensible event stream
+///
+/// In its XML serialization, a log declares the version of the XES standard it conforms to and the
+/// optional features it makes use of. The only feature known by IEEE Std 1849-2016 is
+/// `nested-attributes`, i.e. attributes that contain further attributes.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Header {
+    version: Option<String>,
+    features: Vec<String>,
+}
+
+impl Header {
+    /// Version of the XES standard declared by the log
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Optional features declared by the log
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
 }
 
 /// Provide semantics for sets of attributes
@@ -116,17 +315,99 @@ pub struct Extension {
     uri: String,
 }
 
+impl Extension {
+    /// Create a new extension declaration
+    pub fn new<N: Into<String>, P: Into<String>, U: Into<String>>(
+        name: N,
+        prefix: P,
+        uri: U,
+    ) -> Self {
+        Extension {
+            name: name.into(),
+            prefix: prefix.into(),
+            uri: uri.into(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Prefix of the attribute keys defined by the extension
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// URI of the extension's definition
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+}
+
 /// Global attributes and defaults
 ///
 /// Globals define attributes that have to be present in target scope and provide default values for
-/// such. This may either target traces or events, regardless whether within a trace or not.
+/// such. This may either target traces or events, regardless whether within a trace or not. If
+/// no scope is declared, events are targeted.
 ///
 #[derive(Debug, Clone)]
 pub struct Global {
-    scope: Scope,
+    scope: Option<Scope>,
     attributes: Vec<Attribute>,
 }
 
+impl Global {
+    /// Create a new global without attributes
+    ///
+    /// If no scope is given, none is declared and the global targets events.
+    ///
+    pub fn new(scope: Option<Scope>) -> Self {
+        Global {
+            scope,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Add a mandatory attribute along with its default value
+    pub fn with_attribute(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    /// Targeted scope, taking the default into account
+    pub fn scope(&self) -> &Scope {
+        self.scope.as_ref().unwrap_or(&DEFAULT_SCOPE)
+    }
+
+    /// Scope as declared, if at all
+    pub fn declared_scope(&self) -> Option<&Scope> {
+        self.scope.as_ref()
+    }
+
+    /// Mandatory attributes along with their default values
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+}
+
+/// Look up an attribute by key, falling back to the default of globals in given scope
+fn find_or_global<'a>(
+    attributes: &'a [Attribute],
+    globals: &'a [Global],
+    scope: Scope,
+    key: &str,
+) -> Option<&'a Attribute> {
+    find_attribute(attributes, key).or_else(|| {
+        globals
+            .iter()
+            .filter(|g| *g.scope() == scope)
+            .find_map(|g| find_attribute(&g.attributes, key))
+    })
+}
+
+/// Values of a classifier's attributes, in the order of its keys
+pub type Identity = Vec<String>;
+
 /// Assigns an identity to trace or event
 ///
 /// From [IEEE Std 1849-2016](https://standards.ieee.org/standard/1849-2016.html):
@@ -139,8 +420,115 @@ pub struct Global {
 #[derive(Debug, Clone)]
 pub struct Classifier {
     name: String,
-    scope: Scope,
+    scope: Option<Scope>,
     keys: String,
+    parsed_keys: Vec<String>,
+}
+
+impl Classifier {
+    /// Create a new classifier from its space separated keys
+    ///
+    /// If no scope is given, none is declared and the classifier targets events.
+    ///
+    pub fn new<N: Into<String>, K: Into<String>>(name: N, scope: Option<Scope>, keys: K) -> Self {
+        let keys = keys.into();
+
+        Classifier {
+            name: name.into(),
+            scope,
+            parsed_keys: parse_keys(&keys),
+            keys,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Targeted scope, taking the default into account
+    pub fn scope(&self) -> &Scope {
+        self.scope.as_ref().unwrap_or(&DEFAULT_SCOPE)
+    }
+
+    /// Scope as declared, if at all
+    pub fn declared_scope(&self) -> Option<&Scope> {
+        self.scope.as_ref()
+    }
+
+    /// Attribute keys the classifier is composed of
+    ///
+    /// Keys are separated by white space. Keys that contain white space themselves are enclosed in
+    /// single quotes, e.g. `'my key' concept:name`. An unterminated quote extends to the end.
+    ///
+    pub fn keys(&self) -> &[String] {
+        &self.parsed_keys
+    }
+
+    /// Identity of an event
+    ///
+    /// The values of the classifier's attributes in the order of its keys, e.g. `["a", "complete"]`
+    /// for the keys `concept:name lifecycle:transition`. Missing attributes are taken from the
+    /// event globals. If one is missing anyway or the classifier targets traces, `None` is
+    /// returned.
+    ///
+    pub fn event_identity(&self, event: &Event, globals: &[Global]) -> Option<Identity> {
+        match self.scope() {
+            Scope::Event => self.identity(&event.attributes, globals),
+            Scope::Trace => None,
+        }
+    }
+
+    /// Identity of a trace
+    ///
+    /// Works analogous to `event_identity` for classifiers that target traces.
+    ///
+    pub fn trace_identity(&self, trace: &Trace, globals: &[Global]) -> Option<Identity> {
+        match self.scope() {
+            Scope::Trace => self.identity(&trace.attributes, globals),
+            Scope::Event => None,
+        }
+    }
+
+    fn identity(&self, attributes: &[Attribute], globals: &[Global]) -> Option<Identity> {
+        self.parsed_keys
+            .iter()
+            .map(|key| {
+                find_or_global(attributes, globals, self.scope().clone(), key)
+                    .map(|a| a.value.to_string())
+            })
+            .collect()
+    }
+}
+
+fn parse_keys(keys: &str) -> Vec<String> {
+    let mut parsed: Vec<String> = Vec::new();
+    let mut chars = keys.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let mut key = String::new();
+
+        if c == '\'' {
+            for c in chars.by_ref() {
+                if c == '\'' {
+                    break;
+                }
+                key.push(c);
+            }
+        } else {
+            key.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                key.push(c);
+            }
+        }
+
+        parsed.push(key);
+    }
+
+    parsed
 }
 
 /// Represents an atomic granule of activity that has been observed
@@ -152,16 +540,106 @@ pub struct Classifier {
 /// > events to cases. For this, we will use the combination of a trace classifier and an event
 /// > classifier.
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Event {
     attributes: Vec<Attribute>,
 }
 
-impl Default for Event {
-    fn default() -> Self {
-        Self {
-            attributes: Vec::new(),
-        }
+impl Event {
+    /// Create a new event without attributes
+    pub fn new() -> Self {
+        Event::default()
+    }
+
+    /// Add an attribute
+    pub fn with_attribute(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    /// Look up an attribute by key
+    pub fn attribute(&self, key: &str) -> Option<&Attribute> {
+        find_attribute(&self.attributes, key)
+    }
+
+    pub fn attribute_mut(&mut self, key: &str) -> Option<&mut Attribute> {
+        self.attributes.iter_mut().find(|a| a.key == key)
+    }
+
+    /// Insert an attribute, replacing and returning one with the same key
+    pub fn set_attribute(&mut self, attribute: Attribute) -> Option<Attribute> {
+        set_attribute(&mut self.attributes, attribute)
+    }
+
+    /// Remove and return the attribute with the given key
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Attribute> {
+        remove_attribute(&mut self.attributes, key)
+    }
+
+    /// Look up an attribute by key, falling back to the default value provided by globals
+    pub fn attribute_or_global<'a>(
+        &'a self,
+        key: &str,
+        globals: &'a [Global],
+    ) -> Option<&'a Attribute> {
+        find_or_global(&self.attributes, globals, Scope::Event, key)
+    }
+
+    /// `concept:name` of the event
+    pub fn name<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("concept:name"), meta.globals())?
+            .value
+            .as_string()
+    }
+
+    /// `cost:total` of the event
+    pub fn cost_total(&self, meta: &Meta) -> Option<f64> {
+        self.attribute_or_global(&meta.key("cost:total"), meta.globals())?
+            .value
+            .as_float()
+    }
+
+    /// `identity:id` of the event
+    pub fn id<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("identity:id"), meta.globals())?
+            .value
+            .as_id()
+    }
+
+    /// `time:timestamp` of the event
+    pub fn timestamp<'a>(&'a self, meta: &'a Meta) -> Option<&'a DateTime> {
+        self.attribute_or_global(&meta.key("time:timestamp"), meta.globals())?
+            .value
+            .as_date()
+    }
+
+    /// `lifecycle:transition` of the event
+    pub fn transition<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("lifecycle:transition"), meta.globals())?
+            .value
+            .as_string()
+    }
+
+    /// `org:resource` of the event
+    pub fn resource<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("org:resource"), meta.globals())?
+            .value
+            .as_string()
+    }
+
+    /// `org:role` of the event
+    pub fn role<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("org:role"), meta.globals())?
+            .value
+            .as_string()
     }
 }
 
@@ -173,18 +651,93 @@ impl Default for Event {
 /// > list of events that are related to a single case. The order of the events in this list shall
 /// > be important, as it signifies the order in which the events have been observed.
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Trace {
     attributes: Vec<Attribute>,
     events: Vec<Event>,
 }
 
-impl Default for Trace {
-    fn default() -> Self {
-        Self {
-            attributes: Vec::new(),
-            events: Vec::new(),
-        }
+impl Trace {
+    /// Create a new trace without attributes and events
+    pub fn new() -> Self {
+        Trace::default()
+    }
+
+    /// Add an attribute
+    pub fn with_attribute(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    /// Look up an attribute by key
+    pub fn attribute(&self, key: &str) -> Option<&Attribute> {
+        find_attribute(&self.attributes, key)
+    }
+
+    pub fn attribute_mut(&mut self, key: &str) -> Option<&mut Attribute> {
+        self.attributes.iter_mut().find(|a| a.key == key)
+    }
+
+    /// Insert an attribute, replacing and returning one with the same key
+    pub fn set_attribute(&mut self, attribute: Attribute) -> Option<Attribute> {
+        set_attribute(&mut self.attributes, attribute)
+    }
+
+    /// Remove and return the attribute with the given key
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Attribute> {
+        remove_attribute(&mut self.attributes, key)
+    }
+
+    /// Look up an attribute by key, falling back to the default value provided by globals
+    pub fn attribute_or_global<'a>(
+        &'a self,
+        key: &str,
+        globals: &'a [Global],
+    ) -> Option<&'a Attribute> {
+        find_or_global(&self.attributes, globals, Scope::Trace, key)
+    }
+
+    /// `concept:name` of the trace
+    pub fn name<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("concept:name"), meta.globals())?
+            .value
+            .as_string()
+    }
+
+    /// `cost:total` of the trace
+    pub fn cost_total(&self, meta: &Meta) -> Option<f64> {
+        self.attribute_or_global(&meta.key("cost:total"), meta.globals())?
+            .value
+            .as_float()
+    }
+
+    /// `identity:id` of the trace
+    pub fn id<'a>(&'a self, meta: &'a Meta) -> Option<&'a str> {
+        self.attribute_or_global(&meta.key("identity:id"), meta.globals())?
+            .value
+            .as_id()
+    }
+
+    /// Add an event
+    pub fn with_event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn events_mut(&mut self) -> &mut Vec<Event> {
+        &mut self.events
     }
 }
 
@@ -198,8 +751,9 @@ impl Default for Trace {
 /// > signifies the order in which the events have been observed. If the log contains only events
 /// > and no traces, then the log is also called a stream.
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Log {
+    header: Option<Header>,
     extensions: Vec<Extension>,
     globals: Vec<Global>,
     classifiers: Vec<Classifier>,
@@ -208,44 +762,249 @@ pub struct Log {
     events: Vec<Event>,
 }
 
-impl Default for Log {
-    fn default() -> Self {
-        Self {
-            extensions: Vec::new(),
-            globals: Vec::new(),
-            classifiers: Vec::new(),
-            attributes: Vec::new(),
-            traces: Vec::new(),
-            events: Vec::new(),
+impl Log {
+    /// Create a new, empty log
+    pub fn new() -> Self {
+        Log::default()
+    }
+
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    /// Declare an extension
+    pub fn with_extension(mut self, extension: Extension) -> Self {
+        self.extensions.push(extension);
+        self
+    }
+
+    pub fn extensions(&self) -> &[Extension] {
+        &self.extensions
+    }
+
+    /// Add a global
+    pub fn with_global(mut self, global: Global) -> Self {
+        self.globals.push(global);
+        self
+    }
+
+    pub fn globals(&self) -> &[Global] {
+        &self.globals
+    }
+
+    /// Add a classifier
+    pub fn with_classifier(mut self, classifier: Classifier) -> Self {
+        self.classifiers.push(classifier);
+        self
+    }
+
+    pub fn classifiers(&self) -> &[Classifier] {
+        &self.classifiers
+    }
+
+    /// Look up a classifier by name
+    pub fn classifier(&self, name: &str) -> Option<&Classifier> {
+        self.classifiers.iter().find(|c| c.name == name)
+    }
+
+    /// Add an attribute
+    pub fn with_attribute(mut self, attribute: Attribute) -> Self {
+        self.attributes.push(attribute);
+        self
+    }
+
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    pub fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        &mut self.attributes
+    }
+
+    /// Look up an attribute by key
+    pub fn attribute(&self, key: &str) -> Option<&Attribute> {
+        find_attribute(&self.attributes, key)
+    }
+
+    pub fn attribute_mut(&mut self, key: &str) -> Option<&mut Attribute> {
+        self.attributes.iter_mut().find(|a| a.key == key)
+    }
+
+    /// Insert an attribute, replacing and returning one with the same key
+    pub fn set_attribute(&mut self, attribute: Attribute) -> Option<Attribute> {
+        set_attribute(&mut self.attributes, attribute)
+    }
+
+    /// Remove and return the attribute with the given key
+    pub fn remove_attribute(&mut self, key: &str) -> Option<Attribute> {
+        remove_attribute(&mut self.attributes, key)
+    }
+
+    /// Add a trace
+    pub fn with_trace(mut self, trace: Trace) -> Self {
+        self.traces.push(trace);
+        self
+    }
+
+    pub fn traces(&self) -> &[Trace] {
+        &self.traces
+    }
+
+    pub fn traces_mut(&mut self) -> &mut Vec<Trace> {
+        &mut self.traces
+    }
+
+    /// Add an event that is not part of any trace
+    pub fn with_event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn events_mut(&mut self) -> &mut Vec<Event> {
+        &mut self.events
+    }
+
+    /// Read a log from XES, gzip compressed or not
+    pub fn read<R: io::BufRead>(reader: R) -> error::Result<Self> {
+        let mut log = Log::default();
+        log.consume(&mut XesReader::detect(reader)?)?;
+        Ok(log)
+    }
+
+    /// Read a log from a XES file
+    ///
+    /// Gzip compression is detected by content, regardless of the file's extension.
+    ///
+    pub fn open<P: AsRef<Path>>(path: P) -> error::Result<Self> {
+        Log::read(io::BufReader::new(File::open(path)?))
+    }
+
+    /// Write the log as XES and return the writer
+    pub fn write_xes<W: io::Write>(&self, writer: W) -> error::Result<W> {
+        let mut writer = XesWriter::new(writer, None, None);
+        self.emit(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+
+    /// Render the log as XES
+    pub fn to_xes_string(&self) -> error::Result<String> {
+        Ok(String::from_utf8(self.write_xes(Vec::new())?)?)
+    }
+
+    /// Write the log to a `.xes` or, gzip compressed, `.xes.gz` file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> error::Result<()> {
+        let gzip = is_gzip_path(path.as_ref())?;
+        let file = io::BufWriter::new(File::create(path)?);
+
+        let mut file = if gzip {
+            let mut writer = XesWriter::gzip(file, None, None);
+            self.emit(&mut writer)?;
+            writer.finish()?
+        } else {
+            self.write_xes(file)?
+        };
+
+        Ok(file.flush()?)
+    }
+
+    /// Pass the log's elements to a sink, copying one element at a time
+    fn emit<S: StreamSink>(&self, sink: &mut S) -> error::Result<()> {
+        sink.on_open()?;
+
+        if let Some(header) = &self.header {
+            sink.on_element(Element::Header(header.clone()))?;
+        }
+
+        for extension in self.extensions.iter() {
+            sink.on_element(Element::Extension(extension.clone()))?;
+        }
+
+        for global in self.globals.iter() {
+            sink.on_element(Element::Global(global.clone()))?;
         }
+
+        for classifier in self.classifiers.iter() {
+            sink.on_element(Element::Classifier(classifier.clone()))?;
+        }
+
+        for attribute in self.attributes.iter() {
+            sink.on_element(Element::Attribute(attribute.clone()))?;
+        }
+
+        for trace in self.traces.iter() {
+            sink.on_element(Element::Trace(trace.clone()))?;
+        }
+
+        for event in self.events.iter() {
+            sink.on_element(Element::Event(event.clone()))?;
+        }
+
+        sink.on_close()
     }
 }
 
-impl Into<buffer::Buffer> for Log {
-    fn into(self) -> buffer::Buffer {
+/// Whether a path refers to a gzip compressed (`.xes.gz`) or plain (`.xes`) XES file
+fn is_gzip_path(path: &Path) -> error::Result<bool> {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    if name.ends_with(".xes.gz") {
+        Ok(true)
+    } else if name.ends_with(".xes") {
+        Ok(false)
+    } else {
+        Err(error::Error::FormatError(format!(
+            "{:?}, expected `.xes` or `.xes.gz`",
+            path
+        )))
+    }
+}
+
+impl FromStr for Log {
+    type Err = error::Error;
+
+    /// Parse a log from XES
+    fn from_str(s: &str) -> error::Result<Self> {
+        Log::read(s.as_bytes())
+    }
+}
+
+impl From<Log> for buffer::Buffer {
+    fn from(log: Log) -> Self {
         let mut buffer = buffer::Buffer::default();
 
-        for extension in self.extensions {
+        if let Some(header) = log.header {
+            buffer.push(Ok(Some(Element::Header(header))));
+        }
+
+        for extension in log.extensions {
             buffer.push(Ok(Some(Element::Extension(extension))));
         }
 
-        for global in self.globals {
+        for global in log.globals {
             buffer.push(Ok(Some(Element::Global(global))));
         }
 
-        for classifier in self.classifiers {
+        for classifier in log.classifiers {
             buffer.push(Ok(Some(Element::Classifier(classifier))));
         }
 
-        for attribute in self.attributes {
+        for attribute in log.attributes {
             buffer.push(Ok(Some(Element::Attribute(attribute))));
         }
 
-        for trace in self.traces {
+        for trace in log.traces {
             buffer.push(Ok(Some(Element::Trace(trace))));
         }
 
-        for event in self.events {
+        for event in log.events {
             buffer.push(Ok(Some(Element::Event(event))));
         }
 
@@ -256,6 +1015,7 @@ impl Into<buffer::Buffer> for Log {
 impl StreamSink for Log {
     fn on_element(&mut self, element: Element) -> error::Result<()> {
         match element {
+            Element::Header(h) => self.header = Some(h),
             Element::Extension(e) => self.extensions.push(e),
             Element::Global(g) => self.globals.push(g),
             Element::Classifier(c) => self.classifiers.push(c),
@@ -268,6 +1028,370 @@ impl StreamSink for Log {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_and_inspect() {
+        let event = Event::new()
+            .with_attribute(Attribute::new(
+                "concept:name",
+                AttributeType::String(String::from("a")),
+            ))
+            .with_attribute(Attribute::new("cost:total", AttributeType::Float(1.5)));
+        let trace = Trace::new()
+            .with_attribute(Attribute::new(
+                "concept:name",
+                AttributeType::String(String::from("case 1")),
+            ))
+            .with_event(event.clone())
+            .with_event(event);
+        let mut log = Log::new().with_trace(trace).with_event(Event::new());
+
+        assert_eq!(log.traces().len(), 1);
+        assert_eq!(log.events().len(), 1);
+        assert_eq!(log.traces()[0].events().len(), 2);
+
+        let event = &mut log.traces_mut()[0].events_mut()[1];
+
+        match event.attribute("concept:name").map(|a| a.value()) {
+            Some(AttributeType::String(name)) => assert_eq!(name, "a"),
+            other => panic!("unexpected value {:?}", other),
+        }
+        assert!(event.attribute("concept:fnord").is_none());
+
+        // replace existing attribute
+        let old = event.set_attribute(Attribute::new(
+            "concept:name",
+            AttributeType::String(String::from("b")),
+        ));
+        assert_eq!(old.unwrap().key(), "concept:name");
+        assert_eq!(event.attributes().len(), 2);
+
+        // mutate in place
+        event
+            .attribute_mut("cost:total")
+            .unwrap()
+            .set_value(AttributeType::Float(2.0));
+        match event.attribute("cost:total").unwrap().value() {
+            AttributeType::Float(total) => assert_eq!(*total, 2.0),
+            other => panic!("unexpected value {:?}", other),
+        }
+
+        assert!(event.remove_attribute("cost:total").is_some());
+        assert!(event.remove_attribute("cost:total").is_none());
+        assert_eq!(event.attributes().len(), 1);
+
+        // nested attributes
+        let attribute = Attribute::new("a", AttributeType::Int(1))
+            .with_attribute(Attribute::new("b", AttributeType::Boolean(true)));
+        assert_eq!(attribute.attribute("b").unwrap().key(), "b");
+
+        // round trip through a stream
+        let mut buffer = buffer::Buffer::from(log);
+        let mut copy = Log::new();
+        copy.consume(&mut buffer).unwrap();
+
+        assert_eq!(copy.traces()[0].events()[1].attributes().len(), 1);
+    }
+
+    #[test]
+    fn test_build_meta() {
+        let concept = "http://www.xes-standard.org/concept.xesext";
+        let log = Log::new()
+            .with_extension(Extension::new("Concept", "concept", concept))
+            .with_global(
+                Global::new(Some(Scope::Trace)).with_attribute(Attribute::new(
+                    "concept:name",
+                    AttributeType::String(String::from("__INVALID__")),
+                )),
+            )
+            .with_global(Global::new(None).with_attribute(Attribute::new(
+                "concept:name",
+                AttributeType::String(String::from("__INVALID__")),
+            )))
+            .with_classifier(Classifier::new("Activity", None, "concept:name"))
+            .with_trace(
+                Trace::new()
+                    .with_attribute(Attribute::new(
+                        "concept:name",
+                        AttributeType::String(String::from("case 1")),
+                    ))
+                    .with_event(Event::new().with_attribute(Attribute::new(
+                        "concept:name",
+                        AttributeType::String(String::from("a")),
+                    ))),
+            );
+
+        assert_eq!(log.extensions()[0].prefix(), "concept");
+        assert_eq!(log.extensions()[0].uri(), concept);
+        assert_eq!(log.globals()[0].scope(), &Scope::Trace);
+        assert_eq!(log.globals()[1].declared_scope(), None);
+
+        let event = &log.traces()[0].events()[0];
+        let identity = log
+            .classifier("Activity")
+            .unwrap()
+            .event_identity(event, log.globals());
+        assert_eq!(identity, Some(vec![String::from("a")]));
+
+        // the built log is valid XES
+        let mut validator = stream::xes::XesValidator::new(buffer::Buffer::from(log));
+        stream::consume(&mut validator).unwrap();
+    }
+
+    #[test]
+    fn test_standard_accessors() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <global scope="trace">
+                    <string key="concept:name" value="unknown case"/>
+                </global>
+                <global scope="event">
+                    <string key="concept:name" value="unknown activity"/>
+                    <string key="lifecycle:transition" value="complete"/>
+                    <string key="org:resource" value="nobody"/>
+                </global>
+                <trace>
+                    <float key="cost:total" value="3.5"/>
+                    <id key="identity:id" value="0f3b4e44-4c74-11ea-b77f-2e728ce88125"/>
+                    <event>
+                        <string key="concept:name" value="a"/>
+                        <date key="time:timestamp" value="2010-12-30T11:02:00.000+01:00"/>
+                        <string key="org:role" value="clerk"/>
+                    </event>
+                    <event>
+                        <string key="lifecycle:transition" value="start"/>
+                        <int key="cost:total" value="1"/>
+                    </event>
+                </trace>
+            </log>"#;
+
+        let mut reader = stream::xes::XesReader::from(std::io::BufReader::new(s.as_bytes()));
+        let mut log = Log::new();
+        log.consume(&mut reader).unwrap();
+
+        let meta = Meta::from(&log);
+        let trace = &log.traces()[0];
+        let events = trace.events();
+
+        assert_eq!(trace.name(&meta), Some("unknown case"));
+        assert_eq!(trace.name(&Meta::default()), None);
+        assert_eq!(trace.cost_total(&meta), Some(3.5));
+        assert_eq!(
+            trace.id(&meta),
+            Some("0f3b4e44-4c74-11ea-b77f-2e728ce88125")
+        );
+
+        assert_eq!(events[0].name(&meta), Some("a"));
+        assert_eq!(
+            events[0].timestamp(&meta).map(|t| t.to_rfc3339()),
+            Some(String::from("2010-12-30T11:02:00+01:00"))
+        );
+        assert_eq!(events[0].transition(&meta), Some("complete"));
+        assert_eq!(events[0].resource(&meta), Some("nobody"));
+        assert_eq!(events[0].role(&meta), Some("clerk"));
+        assert_eq!(events[0].id(&meta), None);
+
+        assert_eq!(events[1].name(&meta), Some("unknown activity"));
+        assert_eq!(events[1].transition(&meta), Some("start"));
+        assert_eq!(events[1].timestamp(&meta), None);
+        // mistyped attributes are not converted
+        assert_eq!(events[1].cost_total(&meta), None);
+
+        // standard extensions declared with custom prefixes
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <extension name="Concept" prefix="c"
+                    uri="http://www.xes-standard.org/concept.xesext"/>
+                <extension name="Time" prefix="time" uri="http://www.example.com/time.xesext"/>
+                <trace>
+                    <string key="c:name" value="case 1"/>
+                    <event>
+                        <string key="c:name" value="a"/>
+                        <string key="concept:name" value="b"/>
+                        <date key="time:timestamp" value="2010-12-30T11:02:00.000+01:00"/>
+                    </event>
+                </trace>
+            </log>"#;
+
+        let log: Log = s.parse().unwrap();
+        let meta = Meta::from(&log);
+        let trace = &log.traces()[0];
+
+        assert_eq!(meta.key("concept:name"), "c:name");
+        assert_eq!(meta.key("time:timestamp"), "time:timestamp");
+        assert_eq!(trace.name(&meta), Some("case 1"));
+        assert_eq!(trace.events()[0].name(&meta), Some("a"));
+        assert!(trace.events()[0].timestamp(&meta).is_some());
+    }
+
+    #[test]
+    fn test_classifier_keys() {
+        let param = [
+            ("concept:name", vec!["concept:name"]),
+            (
+                " concept:name  lifecycle:transition ",
+                vec!["concept:name", "lifecycle:transition"],
+            ),
+            (
+                "'my key' concept:name 'other key'",
+                vec!["my key", "concept:name", "other key"],
+            ),
+            ("a'b c", vec!["a'b", "c"]),
+            ("'unterminated key", vec!["unterminated key"]),
+            ("", vec![]),
+            (" ", vec![]),
+        ];
+
+        for (keys, expected) in param.iter() {
+            assert_eq!(
+                Classifier::new("c", Some(Scope::Event), *keys).keys(),
+                *expected
+            );
+        }
+    }
+
+    fn identity(values: &[&str]) -> Option<Identity> {
+        Some(values.iter().map(|v| v.to_string()).collect())
+    }
+
+    #[test]
+    fn test_classifier_identity() {
+        let path = util::expand_static(&["xes", "book", "L1.xes"]);
+        let mut reader = stream::xes::XesReader::from(util::open_buffered(&path));
+        let mut log = Log::new();
+        log.consume(&mut reader).unwrap();
+
+        let globals = log.globals();
+        let event = &log.traces()[0].events()[0];
+        let classifier = log.classifier("MXMLLegacyClassifier").unwrap();
+
+        assert_eq!(
+            classifier.event_identity(event, globals),
+            identity(&["a", "complete"])
+        );
+        assert_eq!(
+            log.classifier("EventName")
+                .unwrap()
+                .event_identity(event, globals),
+            identity(&["a"])
+        );
+        assert!(log.classifier("fnord").is_none());
+
+        // missing attribute without global
+        let classifier = Classifier::new("c", Some(Scope::Event), "concept:name org:role");
+        assert_eq!(classifier.event_identity(event, &[]), None);
+
+        // scope mismatch
+        let classifier = Classifier::new("c", Some(Scope::Trace), "concept:name");
+        assert_eq!(classifier.event_identity(event, globals), None);
+        assert_eq!(
+            classifier.trace_identity(&log.traces()[0], globals),
+            identity(&["Case3.0"])
+        );
+
+        // quoted keys and non-string values
+        let event = Event::new()
+            .with_attribute(Attribute::new("my key", AttributeType::Int(7)))
+            .with_attribute(Attribute::new("b", AttributeType::Boolean(true)));
+        let classifier = Classifier::new("c", Some(Scope::Event), "'my key' b");
+        assert_eq!(
+            classifier.event_identity(&event, &[]),
+            identity(&["7", "true"])
+        );
+
+        // values are not joined, hence don't collide
+        let classifier = Classifier::new("c", None, "x y");
+        let identities: Vec<Option<Identity>> = [("a+b", "c"), ("a", "b+c")]
+            .iter()
+            .map(|(x, y)| {
+                let event = Event::new()
+                    .with_attribute(Attribute::new("x", AttributeType::String(x.to_string())))
+                    .with_attribute(Attribute::new("y", AttributeType::String(y.to_string())));
+                classifier.event_identity(&event, &[])
+            })
+            .collect();
+        assert_ne!(identities[0], identities[1]);
+    }
+
+    #[test]
+    fn test_open_and_save() {
+        let path = util::expand_static(&["xes", "book", "L1.xes"]);
+        let log = Log::open(&path).unwrap();
+
+        assert_eq!(log.traces().len(), 6);
+        assert_eq!(log.classifiers().len(), 3);
+
+        let dir = std::env::temp_dir();
+        for extension in ["xes", "xes.gz"].iter() {
+            let path = dir.join(format!("promi-log-{}.{}", std::process::id(), extension));
+            log.save(&path).unwrap();
+
+            let is_gzip = std::fs::read(&path).unwrap().starts_with(&[0x1f, 0x8b]);
+            let loaded = Log::open(&path);
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(is_gzip, *extension == "xes.gz");
+            assert_eq!(
+                loaded.unwrap().to_xes_string().unwrap(),
+                log.to_xes_string().unwrap()
+            );
+        }
+
+        assert!(matches!(
+            log.save(dir.join("promi-log.txt")),
+            Err(error::Error::FormatError(_))
+        ));
+        assert!(Log::open(util::expand_static(&["xes", "book", "missing.xes"])).is_err());
+
+        // any extension is read, compression is detected by content
+        let path = dir.join(format!("promi-log-{}.txt", std::process::id()));
+        std::fs::write(&path, log.to_xes_string().unwrap()).unwrap();
+        let loaded = Log::open(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().traces().len(), 6);
+        assert!(Log::open(util::expand_static(&["xes", "book"])).is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <trace>
+                    <string key="concept:name" value="Case1.0"/>
+                    <event>
+                        <string key="concept:name" value="A"/>
+                    </event>
+                </trace>
+            </log>"#;
+
+        let log: Log = s.parse().unwrap();
+
+        assert_eq!(log.traces()[0].name(&Meta::default()), Some("Case1.0"));
+        assert!(log
+            .to_xes_string()
+            .unwrap()
+            .contains(r#"<string key="concept:name" value="A"/>"#));
+        assert_eq!(
+            Log::from_str(&log.to_xes_string().unwrap())
+                .unwrap()
+                .traces()[0]
+                .events()
+                .len(),
+            1
+        );
+
+        assert!(
+            r#"<log><event><boolean key="b" value="maybe"/></event></log>"#
+                .parse::<Log>()
+                .is_err()
+        );
+        assert!("".parse::<Log>().is_err());
+    }
+}
+
 /// Useful functions that may panic and are intended for developing promi.
 ///
 pub mod util {
@@ -294,7 +1418,7 @@ pub mod util {
     /// Open a file as `io::BufReader`
     pub fn open_buffered(path: &Path) -> io::BufReader<fs::File> {
         io::BufReader::new(
-            fs::File::open(&path).expect(format!("No such file {:?}", &path).as_str()),
+            fs::File::open(path).unwrap_or_else(|_| panic!("No such file {:?}", path)),
         )
     }
 }
@@ -1,9 +1,15 @@
 //! Buffering event streams.
 //!
+//! `Buffer` stores a stream in memory and is meant to be used within a single thread. Once
+//! drained, it yields the end of the stream. `SharedBuffer` can be shared among threads, e.g. by a
+//! producer and a consumer, and tells apart whether the stream is pending or closed.
+//!
 
 // standard library
 use std::collections::VecDeque;
 use std::fmt::Debug;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 // third party
 
@@ -13,19 +19,11 @@ use crate::stream::{Element, ResOpt, Stream, StreamSink};
 
 /// Consumes a stream and stores it in memory for further processing.
 ///
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Buffer {
     buffer: VecDeque<ResOpt>,
 }
 
-impl Default for Buffer {
-    fn default() -> Self {
-        Self {
-            buffer: VecDeque::new(),
-        }
-    }
-}
-
 impl Stream for Buffer {
     fn next(&mut self) -> ResOpt {
         match self.buffer.pop_front() {
@@ -52,11 +50,212 @@ impl Buffer {
         self.buffer.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
     pub fn push(&mut self, element: ResOpt) {
         self.buffer.push_back(element)
     }
 }
 
+/// State of the producing side of a shared buffer
+#[derive(Debug, Clone, PartialEq)]
+pub enum BufferState {
+    /// More elements may arrive
+    Open,
+    /// The stream was closed regularly
+    Closed,
+    /// The stream was aborted by an error
+    Errored,
+}
+
+/// Result of a read that doesn't wait for elements to arrive
+#[derive(Debug, Clone)]
+pub enum Poll {
+    /// Next element of the stream
+    Ready(Element),
+    /// The buffer is empty but the stream is still open
+    Pending,
+    /// The buffer is empty and the stream ended, either closed or by an error
+    Closed,
+}
+
+#[derive(Debug)]
+struct Shared {
+    buffer: VecDeque<ResOpt>,
+    state: BufferState,
+}
+
+/// Thread safe buffer with shared access
+///
+/// Cloning yields another handle to the same buffer. Producers feed it as stream sink, e.g. by
+/// `consume`, or by `push` and `close`. Consumers either read it as stream, which blocks until
+/// elements arrive and signals the end only once the stream was closed, or use the non-blocking
+/// `poll` and the bounded `poll_timeout`.
+///
+/// An error marks the end of the stream. It's delivered to consumers after all preceding
+/// elements. Since consumers block until the stream ends, producers have to close it eventually.
+///
+#[derive(Debug, Clone)]
+pub struct SharedBuffer {
+    shared: Arc<(Mutex<Shared>, Condvar)>,
+}
+
+impl Default for SharedBuffer {
+    fn default() -> Self {
+        SharedBuffer {
+            shared: Arc::new((
+                Mutex::new(Shared {
+                    buffer: VecDeque::new(),
+                    state: BufferState::Open,
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+}
+
+impl SharedBuffer {
+    /// Create a new, open and empty buffer
+    pub fn new() -> Self {
+        SharedBuffer::default()
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Shared>> {
+        self.shared
+            .0
+            .lock()
+            .map_err(|_| Error::StreamError(String::from("shared buffer is poisoned")))
+    }
+
+    fn append(&self, element: ResOpt, state: BufferState) -> Result<()> {
+        let mut shared = self.lock()?;
+
+        if shared.state != BufferState::Open {
+            return Err(Error::StreamError(format!(
+                "shared buffer is no longer open: {:?}",
+                shared.state
+            )));
+        }
+
+        match element {
+            Ok(None) => (),
+            element => shared.buffer.push_back(element),
+        }
+
+        shared.state = state;
+        self.shared.1.notify_all();
+
+        Ok(())
+    }
+
+    /// Append an element, an error or close the buffer (`Ok(None)`)
+    pub fn push(&self, element: ResOpt) -> Result<()> {
+        let state = match &element {
+            Ok(Some(_)) => BufferState::Open,
+            Ok(None) => BufferState::Closed,
+            Err(_) => BufferState::Errored,
+        };
+
+        self.append(element, state)
+    }
+
+    /// Signal that no more elements will arrive
+    pub fn close(&self) -> Result<()> {
+        self.push(Ok(None))
+    }
+
+    /// State of the producing side
+    pub fn state(&self) -> Result<BufferState> {
+        Ok(self.lock()?.state.clone())
+    }
+
+    /// Number of buffered elements
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.lock()?.buffer.len())
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.lock()?.buffer.is_empty())
+    }
+
+    /// Read the next element if available, without blocking
+    pub fn poll(&self) -> Result<Poll> {
+        let mut shared = self.lock()?;
+        take(&mut shared)
+    }
+
+    /// Read the next element, waiting at most for the given duration
+    pub fn poll_timeout(&self, timeout: Duration) -> Result<Poll> {
+        let deadline = Instant::now() + timeout;
+        let mut shared = self.lock()?;
+
+        loop {
+            match take(&mut shared)? {
+                Poll::Pending => (),
+                other => return Ok(other),
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(Poll::Pending);
+            }
+
+            shared = self
+                .shared
+                .1
+                .wait_timeout(shared, deadline - now)
+                .map_err(|_| Error::StreamError(String::from("shared buffer is poisoned")))?
+                .0;
+        }
+    }
+}
+
+fn take(shared: &mut Shared) -> Result<Poll> {
+    match shared.buffer.pop_front() {
+        Some(Ok(Some(element))) => Ok(Poll::Ready(element)),
+        Some(Err(error)) => Err(error),
+        Some(Ok(None)) | None => match shared.state {
+            BufferState::Open => Ok(Poll::Pending),
+            BufferState::Closed | BufferState::Errored => Ok(Poll::Closed),
+        },
+    }
+}
+
+impl Stream for SharedBuffer {
+    /// Blocks until an element arrives or the stream ends
+    fn next(&mut self) -> ResOpt {
+        let mut shared = self.lock()?;
+
+        loop {
+            match take(&mut shared)? {
+                Poll::Ready(element) => return Ok(Some(element)),
+                Poll::Closed => return Ok(None),
+                Poll::Pending => {
+                    shared = self.shared.1.wait(shared).map_err(|_| {
+                        Error::StreamError(String::from("shared buffer is poisoned"))
+                    })?
+                }
+            }
+        }
+    }
+}
+
+impl StreamSink for SharedBuffer {
+    fn on_element(&mut self, element: Element) -> Result<()> {
+        self.push(Ok(Some(element)))
+    }
+
+    fn on_close(&mut self) -> Result<()> {
+        self.close()
+    }
+
+    fn on_error(&mut self, error: Error) -> Result<()> {
+        self.push(Err(error))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -65,6 +264,7 @@ pub mod tests {
     use std::fs;
     use std::io;
     use std::path::Path;
+    use std::thread;
 
     pub fn load_example(path: &[&str]) -> Buffer {
         let mut root = Path::new(env!("CARGO_MANIFEST_DIR")).join("static");
@@ -89,24 +289,24 @@ pub mod tests {
         let mut buffer_a = load_example(&["xes", "book", "L1.xes"]);
         let mut buffer_b = Buffer::default();
 
-        assert_eq!(buffer_a.len(), 19);
+        assert_eq!(buffer_a.len(), 20);
         assert_eq!(buffer_b.len(), 0);
 
         buffer_b.consume(&mut buffer_a).unwrap();
 
         assert_eq!(buffer_a.len(), 0);
-        assert_eq!(buffer_b.len(), 19);
+        assert_eq!(buffer_b.len(), 20);
 
         let event = crate::Event::default();
         buffer_a.push(Ok(Some(stream::Element::Event(event))));
 
         assert_eq!(buffer_a.len(), 1);
-        assert_eq!(buffer_b.len(), 19);
+        assert_eq!(buffer_b.len(), 20);
 
         buffer_b.consume(&mut buffer_a).unwrap();
 
         assert_eq!(buffer_a.len(), 0);
-        assert_eq!(buffer_b.len(), 20);
+        assert_eq!(buffer_b.len(), 21);
 
         stream::consume(&mut buffer_b).unwrap();
 
@@ -121,4 +321,69 @@ pub mod tests {
 
         assert!(buffer_b.consume(&mut buffer_a).is_err());
     }
+
+    #[test]
+    fn test_shared_buffer() {
+        let mut buffer = SharedBuffer::new();
+        let mut consumer = buffer.clone();
+
+        assert_eq!(buffer.state().unwrap(), BufferState::Open);
+        assert!(matches!(consumer.poll().unwrap(), Poll::Pending));
+        assert!(matches!(
+            consumer.poll_timeout(Duration::from_millis(10)).unwrap(),
+            Poll::Pending
+        ));
+
+        buffer
+            .on_element(stream::Element::Event(crate::Event::default()))
+            .unwrap();
+        assert_eq!(consumer.len().unwrap(), 1);
+        assert!(matches!(consumer.poll().unwrap(), Poll::Ready(_)));
+        assert!(consumer.is_empty().unwrap());
+
+        buffer.close().unwrap();
+        assert_eq!(consumer.state().unwrap(), BufferState::Closed);
+        assert!(matches!(consumer.poll().unwrap(), Poll::Closed));
+        assert!(consumer.next().unwrap().is_none());
+
+        // closed for good
+        assert!(buffer.push(Ok(None)).is_err());
+        assert!(buffer
+            .on_element(stream::Element::Event(crate::Event::default()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_shared_buffer_threads() {
+        let mut producer = SharedBuffer::new();
+        let mut consumer = producer.clone();
+
+        let handle = thread::spawn(move || {
+            let mut source = load_example(&["xes", "book", "L1.xes"]);
+            producer.consume(&mut source).unwrap();
+        });
+
+        let mut buffer = Buffer::default();
+        buffer.consume(&mut consumer).unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(buffer.len(), 20);
+        assert_eq!(consumer.state().unwrap(), BufferState::Closed);
+
+        // errors end the stream after preceding elements
+        let mut producer = SharedBuffer::new();
+        let mut consumer = producer.clone();
+
+        let handle = thread::spawn(move || {
+            let mut source = load_example(&["xes", "non_parsing", "broken_xml.xes"]);
+            assert!(producer.consume(&mut source).is_err());
+        });
+
+        let mut buffer = Buffer::default();
+        assert!(buffer.consume(&mut consumer).is_err());
+        handle.join().unwrap();
+
+        assert_eq!(consumer.state().unwrap(), BufferState::Errored);
+        assert!(matches!(consumer.poll().unwrap(), Poll::Closed));
+    }
 }
@@ -0,0 +1,217 @@
+//! Applying default values of global attributes.
+//!
+//! Globals declare attributes that are mandatory for all traces or events of a stream and provide
+//! default values for them. The `GlobalDefaults` handler completes traces and events that lack such
+//! attributes when registered with an `Observer`.
+//!
+
+// standard library
+use std::fmt;
+
+// third party
+
+// local
+use crate::error::Result;
+use crate::stream::{Handler, Meta};
+use crate::{Attribute, Event, Scope, Trace};
+
+/// Record of a mandatory attribute that was missing and has been defaulted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Defaulted {
+    pub scope: Scope,
+    pub trace: Option<usize>,
+    pub event: Option<usize>,
+    pub key: String,
+}
+
+impl fmt::Display for Defaulted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing {:?} defaulted", self.key)?;
+
+        if let Some(trace) = self.trace {
+            write!(f, " in trace {}", trace)?;
+        }
+
+        if let Some(event) = self.event {
+            write!(f, " in event {}", event)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Fill traces and events with the default values of globals
+///
+/// For each global attribute of the matching scope that is missing, a copy of the global's
+/// attribute is added. By default, this happens silently. In report mode, a record is kept of each
+/// defaulted attribute, which can be released once done.
+///
+/// Traces are counted in stream order. Events are counted per trace, or among the events outside
+/// of traces.
+///
+#[derive(Debug, Default)]
+pub struct GlobalDefaults {
+    report: bool,
+    defaulted: Vec<Defaulted>,
+    ct_trace: usize,
+    ct_event: usize,
+    ct_log_event: usize,
+}
+
+impl GlobalDefaults {
+    /// Create a new handler that fills in defaults silently
+    pub fn new() -> Self {
+        GlobalDefaults::default()
+    }
+
+    /// Create a new handler that records each defaulted attribute
+    pub fn reporting() -> Self {
+        GlobalDefaults {
+            report: true,
+            ..GlobalDefaults::default()
+        }
+    }
+
+    /// Records collected so far
+    pub fn defaulted(&self) -> &[Defaulted] {
+        &self.defaulted
+    }
+
+    /// Release collected records
+    pub fn release(&mut self) -> Vec<Defaulted> {
+        std::mem::take(&mut self.defaulted)
+    }
+
+    fn apply(
+        &mut self,
+        attributes: &mut Vec<Attribute>,
+        meta: &Meta,
+        scope: Scope,
+        trace: Option<usize>,
+        event: Option<usize>,
+    ) {
+        for global in meta.globals().iter().filter(|g| *g.scope() == scope) {
+            for attribute in global.attributes() {
+                if attributes.iter().any(|a| a.key() == attribute.key()) {
+                    continue;
+                }
+
+                attributes.push(attribute.clone());
+
+                if self.report {
+                    self.defaulted.push(Defaulted {
+                        scope: scope.clone(),
+                        trace,
+                        event,
+                        key: attribute.key().to_string(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Handler for GlobalDefaults {
+    fn trace(&mut self, mut trace: Trace, meta: &Meta) -> Result<Option<Trace>> {
+        let location = Some(self.ct_trace);
+        self.ct_trace += 1;
+        self.ct_event = 0;
+
+        self.apply(trace.attributes_mut(), meta, Scope::Trace, location, None);
+
+        Ok(Some(trace))
+    }
+
+    fn event(&mut self, mut event: Event, in_trace: bool, meta: &Meta) -> Result<Option<Event>> {
+        let location = if in_trace {
+            self.ct_event += 1;
+            (self.ct_trace.checked_sub(1), Some(self.ct_event - 1))
+        } else {
+            self.ct_log_event += 1;
+            (None, Some(self.ct_log_event - 1))
+        };
+
+        self.apply(
+            event.attributes_mut(),
+            meta,
+            Scope::Event,
+            location.0,
+            location.1,
+        );
+
+        Ok(Some(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::xes::XesReader;
+    use crate::stream::{Observer, StreamSink};
+    use crate::Log;
+    use std::io;
+
+    #[test]
+    fn test_global_defaults() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <global scope="trace">
+                    <string key="concept:name" value="unknown case"/>
+                </global>
+                <global scope="event">
+                    <string key="concept:name" value="unknown activity"/>
+                    <string key="lifecycle:transition" value="complete"/>
+                </global>
+                <trace>
+                    <event>
+                        <string key="concept:name" value="a"/>
+                    </event>
+                    <event>
+                        <string key="concept:name" value="b"/>
+                        <string key="lifecycle:transition" value="start"/>
+                    </event>
+                </trace>
+                <trace>
+                    <string key="concept:name" value="case 2"/>
+                </trace>
+                <event/>
+            </log>"#;
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut observer = Observer::new(reader);
+        observer.register(GlobalDefaults::reporting());
+
+        let mut log = Log::new();
+        log.consume(&mut observer).unwrap();
+
+        let meta = Meta::from(&log);
+        let events = log.traces()[0].events();
+
+        assert_eq!(log.traces()[0].name(&Meta::default()), Some("unknown case"));
+        assert_eq!(log.traces()[1].name(&Meta::default()), Some("case 2"));
+        assert_eq!(events[0].name(&Meta::default()), Some("a"));
+        assert_eq!(events[0].transition(&Meta::default()), Some("complete"));
+        assert_eq!(events[1].transition(&meta), Some("start"));
+        assert_eq!(
+            log.events()[0].name(&Meta::default()),
+            Some("unknown activity")
+        );
+
+        let key = |k: &str| k.to_string();
+        let expected = [
+            (Scope::Trace, Some(0), None, key("concept:name")),
+            (Scope::Event, Some(0), Some(0), key("lifecycle:transition")),
+            (Scope::Event, None, Some(0), key("concept:name")),
+            (Scope::Event, None, Some(0), key("lifecycle:transition")),
+        ];
+        let defaulted: Vec<(Scope, Option<usize>, Option<usize>, String)> = observer
+            .release()
+            .unwrap()
+            .release()
+            .into_iter()
+            .map(|d| (d.scope, d.trace, d.event, d.key))
+            .collect();
+
+        assert_eq!(defaulted, expected);
+    }
+}
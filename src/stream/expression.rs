@@ -0,0 +1,695 @@
+//! Declarative filter expressions.
+//!
+//! Expressions describe conditions on attributes of traces or events and compile into a `Filter`.
+//! A condition compares the value of the attribute with the given key to literals:
+//!
+//! | condition                    | example                                           |
+//! |------------------------------|---------------------------------------------------|
+//! | comparison                   | `cost:total >= 12.5`, `org:resource != "Pete"`    |
+//! | set membership               | `concept:name in ("a", "b")`                      |
+//! | regular expression           | `concept:name matches "^check.*"`                 |
+//!
+//! Comparison operators are `==`, `!=`, `<`, `<=`, `>` and `>=`. Conditions are combined by
+//! `and`, `or` and `not` while parentheses group them. Literals are double quoted strings,
+//! integers, floats, booleans (`true`, `false`) and dates, either as RFC 3339 timestamps or plain
+//! dates such as `2010-12-01` that are taken as midnight UTC. Keys containing white space or
+//! special characters are enclosed in single quotes, e.g. `'my key' == 1`. Within quotes, a
+//! backslash escapes the quote and itself, other backslashes are kept as they are. Expressions
+//! nest at most 256 levels deep, counting parentheses, `not` and each further `and` or `or`.
+//!
+//! A condition only holds if the attribute is present, possibly as global default, and its value
+//! is comparable to the literal. Strings and ids compare to strings, ints and floats to numbers,
+//! dates to dates and booleans to booleans. Hence, `org:resource != "Pete"` does not hold for
+//! events lacking an `org:resource` attribute. Keys of standard extensions are translated to the
+//! prefixes a log declares, e.g. `concept:name` matches `c:name` if the log declares the concept
+//! extension with prefix `c`.
+//!
+//! ```
+//! use promi::stream::expression::Expression;
+//!
+//! let expression = Expression::parse(
+//!     r#"concept:name in ("a", "b") and time:timestamp >= 2010-12-01"#
+//! ).unwrap();
+//! let filter = expression.event_filter();
+//! ```
+//!
+
+// standard library
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+// third party
+use chrono::{NaiveDate, TimeZone, Utc};
+use regex::Regex;
+
+// local
+use crate::error::{Error, Result};
+use crate::stream::filter::Filter;
+use crate::stream::Meta;
+use crate::{Attribute, AttributeType, DateTime, Event, Trace};
+
+/// Maximum nesting depth, deeper expressions are rejected instead of exhausting the stack
+const MAX_DEPTH: usize = 256;
+
+/// Comparison operators
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    fn holds(&self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::Ne => ordering != Ordering::Equal,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::Le => ordering != Ordering::Greater,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Literal values attributes are compared to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Boolean(bool),
+    Date(DateTime),
+}
+
+impl Literal {
+    fn from_word(word: &str) -> Option<Self> {
+        if word == "true" || word == "false" {
+            return Some(Literal::Boolean(word == "true"));
+        }
+
+        if let Ok(value) = word.parse::<i64>() {
+            return Some(Literal::Int(value));
+        }
+
+        if let Ok(value) = word.parse::<f64>() {
+            return Some(Literal::Float(value));
+        }
+
+        if let Ok(value) = DateTime::parse_from_rfc3339(word) {
+            return Some(Literal::Date(value));
+        }
+
+        if let Ok(value) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+            let midnight = Utc.from_utc_datetime(&value.and_hms_opt(0, 0, 0)?);
+            return Some(Literal::Date(midnight.into()));
+        }
+
+        None
+    }
+
+    /// Order an attribute value relative to the literal, if comparable
+    fn compare(&self, value: &AttributeType) -> Option<Ordering> {
+        match (value, self) {
+            (AttributeType::String(v), Literal::String(l))
+            | (AttributeType::Id(v), Literal::String(l)) => Some(v.as_str().cmp(l.as_str())),
+            (AttributeType::Int(v), Literal::Int(l)) => Some(v.cmp(l)),
+            (AttributeType::Int(v), Literal::Float(l)) => (*v as f64).partial_cmp(l),
+            (AttributeType::Float(v), Literal::Int(l)) => v.partial_cmp(&(*l as f64)),
+            (AttributeType::Float(v), Literal::Float(l)) => v.partial_cmp(l),
+            (AttributeType::Boolean(v), Literal::Boolean(l)) => Some(v.cmp(l)),
+            (AttributeType::Date(v), Literal::Date(l)) => Some(v.cmp(l)),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed filter expression
+#[derive(Debug, Clone)]
+pub enum Expression {
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Compare {
+        key: String,
+        operator: Operator,
+        literal: Literal,
+    },
+    In {
+        key: String,
+        literals: Vec<Literal>,
+    },
+    Matches {
+        key: String,
+        regex: Regex,
+    },
+}
+
+impl Expression {
+    /// Parse an expression from its string representation
+    pub fn parse(expression: &str) -> Result<Self> {
+        let tokens = tokenize(expression)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            position: 0,
+            depth: 0,
+        };
+
+        let parsed = parser.or()?;
+
+        match parser.peek() {
+            None => Ok(parsed),
+            Some(token) => Err(parser.error(&format!("unexpected token {:?}", token))),
+        }
+    }
+
+    /// Evaluate the expression on attributes provided by `lookup`
+    pub fn evaluate<'a, F>(&self, lookup: &F) -> bool
+    where
+        F: Fn(&str) -> Option<&'a Attribute>,
+    {
+        let value = |key: &str| lookup(key).map(|a| a.value());
+
+        match self {
+            Expression::And(left, right) => left.evaluate(lookup) && right.evaluate(lookup),
+            Expression::Or(left, right) => left.evaluate(lookup) || right.evaluate(lookup),
+            Expression::Not(expression) => !expression.evaluate(lookup),
+            Expression::Compare {
+                key,
+                operator,
+                literal,
+            } => value(key)
+                .and_then(|v| literal.compare(v))
+                .is_some_and(|o| operator.holds(o)),
+            Expression::In { key, literals } => value(key).is_some_and(|v| {
+                literals
+                    .iter()
+                    .any(|l| l.compare(v) == Some(Ordering::Equal))
+            }),
+            Expression::Matches { key, regex } => match value(key) {
+                Some(AttributeType::String(v)) | Some(AttributeType::Id(v)) => regex.is_match(v),
+                _ => false,
+            },
+        }
+    }
+
+    /// Whether the expression holds for an event, taking event globals and the prefixes declared
+    /// for standard extensions into account
+    pub fn matches_event(&self, event: &Event, meta: &Meta) -> bool {
+        self.evaluate(&|key| event.attribute_or_global(&meta.key(key), meta.globals()))
+    }
+
+    /// Whether the expression holds for a trace, taking trace globals and the prefixes declared
+    /// for standard extensions into account
+    pub fn matches_trace(&self, trace: &Trace, meta: &Meta) -> bool {
+        self.evaluate(&|key| trace.attribute_or_global(&meta.key(key), meta.globals()))
+    }
+
+    /// Compile into a filter that drops events the expression doesn't hold for
+    pub fn event_filter(self) -> Filter {
+        Filter::new().event(move |event, meta| self.matches_event(event, meta))
+    }
+
+    /// Compile into a filter that drops traces the expression doesn't hold for
+    pub fn trace_filter(self) -> Filter {
+        Filter::new().trace(move |trace, meta| self.matches_trace(trace, meta))
+    }
+}
+
+impl FromStr for Expression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Expression::parse(s)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Comma,
+    Operator(Operator),
+    String(String),
+    Key(String),
+    Word(String),
+}
+
+fn tokenize(expression: &str) -> Result<Vec<(usize, Token)>> {
+    let mut tokens: Vec<(usize, Token)> = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            ',' => Token::Comma,
+            '=' | '!' | '<' | '>' => {
+                let equals = chars.next_if(|(_, c)| *c == '=').is_some();
+
+                Token::Operator(match (c, equals) {
+                    ('=', true) => Operator::Eq,
+                    ('!', true) => Operator::Ne,
+                    ('<', true) => Operator::Le,
+                    ('<', false) => Operator::Lt,
+                    ('>', true) => Operator::Ge,
+                    ('>', false) => Operator::Gt,
+                    _ => {
+                        return Err(Error::ExpressionError(format!(
+                            "invalid operator at position {}",
+                            position
+                        )))
+                    }
+                })
+            }
+            '"' | '\'' => {
+                let mut value = String::new();
+                let mut terminated = false;
+
+                while let Some((_, d)) = chars.next() {
+                    match d {
+                        '\\' => match chars.next() {
+                            Some((_, e)) if e == c || e == '\\' => value.push(e),
+                            Some((_, e)) => {
+                                value.push('\\');
+                                value.push(e);
+                            }
+                            None => break,
+                        },
+                        d if d == c => {
+                            terminated = true;
+                            break;
+                        }
+                        d => value.push(d),
+                    }
+                }
+
+                if !terminated {
+                    return Err(Error::ExpressionError(format!(
+                        "unterminated quote at position {}",
+                        position
+                    )));
+                }
+
+                if c == '"' {
+                    Token::String(value)
+                } else {
+                    Token::Key(value)
+                }
+            }
+            c => {
+                let mut word = c.to_string();
+
+                while let Some((_, d)) = chars.next_if(|(_, d)| !is_delimiter(*d)) {
+                    word.push(d);
+                }
+
+                Token::Word(word)
+            }
+        };
+
+        tokens.push((position, token));
+    }
+
+    Ok(tokens)
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "()=!<>,\"'".contains(c)
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    position: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> Error {
+        match self.tokens.get(self.position) {
+            Some((position, _)) => {
+                Error::ExpressionError(format!("{} at position {}", message, position))
+            }
+            None => Error::ExpressionError(format!("{} at end of expression", message)),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        if self.peek() == Some(&expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {:?}", expected)))
+        }
+    }
+
+    fn descend(&mut self) -> Result<()> {
+        self.depth += 1;
+
+        if self.depth > MAX_DEPTH {
+            Err(self.error(&format!(
+                "expression nested deeper than {} levels",
+                MAX_DEPTH
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression> {
+        let depth = self.depth;
+        let mut expression = self.and()?;
+
+        while self.keyword("or") {
+            self.descend()?;
+            expression = Expression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<Expression> {
+        let depth = self.depth;
+        let mut expression = self.not()?;
+
+        while self.keyword("and") {
+            self.descend()?;
+            expression = Expression::And(Box::new(expression), Box::new(self.not()?));
+        }
+
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn not(&mut self) -> Result<Expression> {
+        if self.keyword("not") {
+            self.descend()?;
+            let expression = Expression::Not(Box::new(self.not()?));
+            self.depth -= 1;
+            Ok(expression)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression> {
+        if self.peek() == Some(&Token::Open) {
+            self.position += 1;
+            self.descend()?;
+            let expression = self.or()?;
+            self.expect(Token::Close)?;
+            self.depth -= 1;
+            return Ok(expression);
+        }
+
+        let key = match self.peek() {
+            Some(Token::Word(key)) | Some(Token::Key(key)) => {
+                self.position += 1;
+                key.clone()
+            }
+            _ => return Err(self.error("expected attribute key")),
+        };
+
+        if self.keyword("in") {
+            self.expect(Token::Open)?;
+            let mut literals = vec![self.literal()?];
+
+            while self.peek() == Some(&Token::Comma) {
+                self.position += 1;
+                literals.push(self.literal()?);
+            }
+
+            self.expect(Token::Close)?;
+
+            return Ok(Expression::In { key, literals });
+        }
+
+        if self.keyword("matches") {
+            return match self.peek() {
+                Some(Token::String(pattern)) => match Regex::new(pattern) {
+                    Ok(regex) => {
+                        self.position += 1;
+                        Ok(Expression::Matches { key, regex })
+                    }
+                    Err(e) => Err(self.error(&format!("invalid regular expression: {}", e))),
+                },
+                _ => Err(self.error("expected regular expression")),
+            };
+        }
+
+        match self.peek() {
+            Some(Token::Operator(operator)) => {
+                self.position += 1;
+                Ok(Expression::Compare {
+                    key,
+                    operator: operator.clone(),
+                    literal: self.literal()?,
+                })
+            }
+            _ => Err(self.error("expected operator")),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Literal> {
+        let literal = match self.peek() {
+            Some(Token::String(value)) => Some(Literal::String(value.clone())),
+            Some(Token::Word(word)) => Literal::from_word(word),
+            _ => None,
+        };
+
+        match literal {
+            Some(literal) => {
+                self.next();
+                Ok(literal)
+            }
+            None => Err(self.error("expected literal")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::buffer::tests::load_example;
+    use crate::stream::StreamSink;
+    use crate::Log;
+
+    fn filter_example(filter: Filter) -> Log {
+        let mut log = Log::default();
+        log.consume(&mut filter.stream(load_example(&["xes", "book", "L1.xes"])))
+            .unwrap();
+        log
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let event = Event::new()
+            .with_attribute(Attribute::new(
+                "concept:name",
+                AttributeType::String(String::from("check ticket")),
+            ))
+            .with_attribute(Attribute::new("cost:total", AttributeType::Float(12.5)))
+            .with_attribute(Attribute::new("my key", AttributeType::Int(3)))
+            .with_attribute(Attribute::new("flag", AttributeType::Boolean(true)))
+            .with_attribute(Attribute::new(
+                "time:timestamp",
+                AttributeType::Date(
+                    DateTime::parse_from_rfc3339("2010-12-30T11:02:00.000+01:00").unwrap(),
+                ),
+            ));
+        let default = Attribute::new("org:resource", AttributeType::String(String::from("Pete")));
+        let lookup = |key: &str| {
+            event
+                .attribute(key)
+                .or(Some(&default).filter(|d| d.key() == key))
+        };
+
+        let param = [
+            (r#"concept:name == "check ticket""#, true),
+            (r#"concept:name != "check ticket""#, false),
+            (r#"concept:name < "d""#, true),
+            (r#"concept:name in ("a", "check ticket")"#, true),
+            (r#"concept:name in ("a", "b")"#, false),
+            (r#"concept:name matches "^check\s""#, true),
+            (r#"concept:name matches "^ticket""#, false),
+            ("cost:total > 12", true),
+            ("cost:total >= 12.5", true),
+            ("cost:total < 12.5", false),
+            ("'my key' <= 3", true),
+            (r#"'my\' key' <= 3"#, false),
+            ("'my key' == 3.0", true),
+            ("flag == true", true),
+            ("flag != true", false),
+            ("time:timestamp >= 2010-12-01", true),
+            ("time:timestamp > 2010-12-30T10:01:00Z", true),
+            ("time:timestamp > 2010-12-30T10:02:00Z", false),
+            (r#"org:resource == "Pete""#, true),
+            (r#"org:role != "Pete""#, false),
+            (r#"cost:total == "12.5""#, false),
+            (r#"not org:role == "Pete""#, true),
+            (r#"flag == false or cost:total > 10"#, true),
+            (r#"flag == false or cost:total > 20"#, false),
+            (r#"flag == true and cost:total > 20"#, false),
+            (
+                r#"NOT (flag == false OR cost:total > 20) AND 'my key' in (1, 2, 3)"#,
+                true,
+            ),
+            (r#"not not flag == true"#, true),
+        ];
+
+        for (expression, expected) in param.iter() {
+            let parsed = Expression::parse(expression).unwrap();
+            assert_eq!(parsed.evaluate(&lookup), *expected, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let param = [
+            "",
+            "concept:name",
+            "concept:name ==",
+            "concept:name = \"a\"",
+            "concept:name == a",
+            "concept:name == \"a",
+            "concept:name in \"a\"",
+            "concept:name in (\"a\",)",
+            "concept:name matches \"(\"",
+            "(concept:name == \"a\"",
+            "concept:name == \"a\" and",
+            "concept:name == \"a\" concept:name",
+            "== \"a\"",
+        ];
+
+        for expression in param.iter() {
+            match Expression::parse(expression) {
+                Err(Error::ExpressionError(_)) => (),
+                other => panic!("{:?} yields {:?}", expression, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_depth() {
+        let condition = "flag == true";
+        let nested = |n: usize, prefix: &str, suffix: &str| {
+            format!("{}{}{}", prefix.repeat(n), condition, suffix.repeat(n))
+        };
+        let chained = |n: usize, operator: &str| vec![condition; n + 1].join(operator);
+
+        for n in [MAX_DEPTH, MAX_DEPTH + 1, 100_000].iter() {
+            let param = [
+                nested(*n, "(", ")"),
+                nested(*n, "not ", ""),
+                chained(*n, " and "),
+                chained(*n, " or "),
+            ];
+
+            for expression in param.iter() {
+                match Expression::parse(expression) {
+                    Ok(_) => assert!(*n <= MAX_DEPTH),
+                    Err(Error::ExpressionError(message)) => {
+                        assert!(*n > MAX_DEPTH);
+                        assert!(message.contains("nested"), "{}", message);
+                    }
+                    Err(e) => panic!("unexpected error {:?}", e),
+                }
+            }
+        }
+
+        // the depth is restored after each group, the chain itself adds two levels
+        let expression = vec![nested(MAX_DEPTH - 2, "(", ")"); 3].join(" and ");
+        assert!(Expression::parse(&expression).is_ok());
+    }
+
+    #[test]
+    fn test_filter_stream() {
+        let expression: Expression =
+            r#"concept:name in ("a", "d") and time:timestamp >= 2010-10-27"#
+                .parse()
+                .unwrap();
+        let log = filter_example(expression.event_filter());
+
+        let events: Vec<usize> = log.traces().iter().map(|t| t.events().len()).collect();
+        assert_eq!(events, vec![2; 6]);
+
+        let expression = Expression::parse("time:timestamp >= 2010-10-28").unwrap();
+        let log = filter_example(expression.event_filter());
+
+        assert!(log.traces().iter().all(|t| t.events().is_empty()));
+
+        let expression = Expression::parse(r#"concept:name matches "^Case1\.""#).unwrap();
+        let log = filter_example(expression.trace_filter());
+
+        assert_eq!(log.traces().len(), 3);
+
+        // event globals provide "complete" as default transition
+        let expression = Expression::parse(r#"lifecycle:transition != "complete""#).unwrap();
+        let log = filter_example(expression.event_filter());
+
+        assert!(log.traces().iter().all(|t| t.events().is_empty()));
+    }
+
+    #[test]
+    fn test_declared_prefix() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <extension name="Concept" prefix="c"
+                    uri="http://www.xes-standard.org/concept.xesext"/>
+                <trace>
+                    <string key="c:name" value="case 1"/>
+                    <event>
+                        <string key="c:name" value="a"/>
+                    </event>
+                    <event>
+                        <string key="c:name" value="b"/>
+                    </event>
+                </trace>
+            </log>"#;
+
+        let log: Log = s.parse().unwrap();
+        let meta = Meta::from(&log);
+        let trace = &log.traces()[0];
+
+        let expression = Expression::parse(r#"concept:name == "case 1""#).unwrap();
+        assert!(expression.matches_trace(trace, &meta));
+
+        let expression = Expression::parse(r#"concept:name == "a""#).unwrap();
+        let matches: Vec<bool> = trace
+            .events()
+            .iter()
+            .map(|e| expression.matches_event(e, &meta))
+            .collect();
+        assert_eq!(matches, vec![true, false]);
+
+        // the declared key itself is looked up literally
+        let expression = Expression::parse(r#"c:name == "b""#).unwrap();
+        assert!(expression.matches_event(&trace.events()[1], &meta));
+    }
+}
@@ -1,10 +1,356 @@
 //! Filtering event streams.
 //!
+//! A filter holds an arbitrary number of predicates over traces and events. An element passes if
+//! any of the predicates of its kind matches (logical disjunction). Logical conjunction is achieved
+//! by chaining filters.
+//!
+//! Further, traces and events can be cut to a time window with respect to their `time:timestamp`
+//! attribute.
+//!
 
 // standard library
+use std::fmt;
 
 // third party
 
 // local
+use crate::error::Result;
+use crate::stream::{Handler, Meta, Observer, Stream};
+use crate::{DateTime, Event, Trace};
+
+/// Predicate over traces
+pub type TracePredicate = Box<dyn Fn(&Trace, &Meta) -> bool>;
+
+/// Predicate over events
+pub type EventPredicate = Box<dyn Fn(&Event, &Meta) -> bool>;
+
+/// Drop traces and events that don't match any predicate
+///
+/// Traces are dropped as a whole if none of the trace predicates matches. Events are dropped
+/// individually, regardless whether they are part of a trace or not, if none of the event
+/// predicates matches. Without any predicates of a kind, all elements of that kind pass.
+///
+/// The filter acts as a `Handler`. To use it as stream adapter, see `Filter::stream`.
+///
+/// ```
+/// use promi::stream::filter::Filter;
+///
+/// let filter = Filter::new()
+///     .event(|event, meta| event.name(meta) == Some("a"))
+///     .event(|event, meta| event.resource(meta) == Some("Pete"));
+/// ```
+///
+#[derive(Default)]
+pub struct Filter {
+    trace_predicates: Vec<TracePredicate>,
+    event_predicates: Vec<EventPredicate>,
+}
+
+impl Filter {
+    /// Create a new filter that lets everything pass
+    pub fn new() -> Self {
+        Filter::default()
+    }
+
+    /// Add a trace predicate
+    pub fn trace<F: Fn(&Trace, &Meta) -> bool + 'static>(mut self, predicate: F) -> Self {
+        self.trace_predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Add an event predicate
+    pub fn event<F: Fn(&Event, &Meta) -> bool + 'static>(mut self, predicate: F) -> Self {
+        self.event_predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Whether the trace passes the filter
+    pub fn matches_trace(&self, trace: &Trace, meta: &Meta) -> bool {
+        self.trace_predicates.is_empty() || self.trace_predicates.iter().any(|p| p(trace, meta))
+    }
+
+    /// Whether the event passes the filter
+    pub fn matches_event(&self, event: &Event, meta: &Meta) -> bool {
+        self.event_predicates.is_empty() || self.event_predicates.iter().any(|p| p(event, meta))
+    }
+
+    /// Apply the filter to a stream
+    pub fn stream<T: Stream>(self, stream: T) -> Observer<T, Filter> {
+        let mut observer = Observer::new(stream);
+        observer.register(self);
+        observer
+    }
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Filter")
+            .field("trace_predicates", &self.trace_predicates.len())
+            .field("event_predicates", &self.event_predicates.len())
+            .finish()
+    }
+}
+
+impl Handler for Filter {
+    fn trace(&mut self, trace: Trace, meta: &Meta) -> Result<Option<Trace>> {
+        if self.matches_trace(&trace, meta) {
+            Ok(Some(trace))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn event(&mut self, event: Event, _in_trace: bool, meta: &Meta) -> Result<Option<Event>> {
+        if self.matches_event(&event, meta) {
+            Ok(Some(event))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// How traces are related to a time window
+#[derive(Debug, Clone, PartialEq)]
+pub enum WindowMode {
+    /// Keep traces whose events all lie within the window
+    Contained,
+    /// Keep traces with at least one event at or after the window start and one at or before the
+    /// window end, i.e. traces whose time span overlaps the window
+    Intersecting,
+    /// Keep only events within the window and drop traces that end up empty
+    Trimmed,
+}
+
+/// Cut a stream to a time window
+///
+/// The window is given by optional, inclusive bounds and compared to the `time:timestamp`
+/// attribute of events, falling back to the default of event globals. Comparison takes timezone
+/// offsets into account. Events without timestamp are ignored when computing the time span of a
+/// trace, traces without any timestamp are dropped. Events outside of traces are kept if they lie
+/// within the window, regardless of the mode.
+///
+#[derive(Debug, Clone)]
+pub struct TimeWindow {
+    mode: WindowMode,
+    start: Option<DateTime>,
+    end: Option<DateTime>,
+}
+
+impl TimeWindow {
+    /// Create a new time window, an absent bound leaves the window open on that side
+    pub fn new(mode: WindowMode, start: Option<DateTime>, end: Option<DateTime>) -> Self {
+        TimeWindow { mode, start, end }
+    }
+
+    /// Whether a point in time lies within the window
+    pub fn contains(&self, timestamp: &DateTime) -> bool {
+        self.start.map_or(true, |s| *timestamp >= s) && self.end.map_or(true, |e| *timestamp <= e)
+    }
+
+    /// Apply the time window to a stream
+    pub fn stream<T: Stream>(self, stream: T) -> Observer<T, TimeWindow> {
+        let mut observer = Observer::new(stream);
+        observer.register(self);
+        observer
+    }
+
+    fn contains_event(&self, event: &Event, meta: &Meta) -> bool {
+        event.timestamp(meta).is_some_and(|t| self.contains(t))
+    }
+}
+
+impl Handler for TimeWindow {
+    fn trace(&mut self, mut trace: Trace, meta: &Meta) -> Result<Option<Trace>> {
+        let timestamps = trace.events().iter().filter_map(|e| e.timestamp(meta));
+
+        let keep = match self.mode {
+            WindowMode::Contained => {
+                let mut timestamps = timestamps.peekable();
+                timestamps.peek().is_some() && timestamps.all(|t| self.contains(t))
+            }
+            WindowMode::Intersecting => match (timestamps.clone().min(), timestamps.max()) {
+                (Some(first), Some(last)) => {
+                    self.start.map_or(true, |s| *last >= s)
+                        && self.end.map_or(true, |e| *first <= e)
+                }
+                _ => false,
+            },
+            WindowMode::Trimmed => {
+                trace.events_mut().retain(|e| self.contains_event(e, meta));
+                !trace.events().is_empty()
+            }
+        };
+
+        if keep {
+            Ok(Some(trace))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn event(&mut self, event: Event, in_trace: bool, meta: &Meta) -> Result<Option<Event>> {
+        if in_trace || self.contains_event(&event, meta) {
+            Ok(Some(event))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::buffer::tests::load_example;
+    use crate::stream::buffer::Buffer;
+    use crate::stream::StreamSink;
+    use crate::{Attribute, AttributeType, Log};
+
+    fn filter_example(filter: Filter) -> Log {
+        let mut log = Log::default();
+        log.consume(&mut filter.stream(load_example(&["xes", "book", "L1.xes"])))
+            .unwrap();
+        log
+    }
+
+    fn names(trace: &Trace) -> Vec<String> {
+        trace
+            .events()
+            .iter()
+            .map(|e| e.name(&Meta::default()).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_pass_all() {
+        let log = filter_example(Filter::new());
+
+        assert_eq!(log.traces().len(), 6);
+        assert_eq!(names(&log.traces()[0]), vec!["a", "e", "d"]);
+    }
+
+    #[test]
+    fn test_trace_filter() {
+        let log = filter_example(
+            Filter::new()
+                .trace(|t, m| t.name(m) == Some("Case3.0"))
+                .trace(|t, m| t.name(m).is_some_and(|n| n.ends_with(".2"))),
+        );
+
+        let meta = Meta::from(&log);
+        let traces: Vec<&str> = log
+            .traces()
+            .iter()
+            .map(|t| t.name(&meta).unwrap())
+            .collect();
+
+        assert_eq!(traces, vec!["Case3.0", "Case1.2"]);
+    }
+
+    #[test]
+    fn test_event_filter() {
+        let log = filter_example(
+            Filter::new()
+                .event(|e, m| e.name(m) == Some("a"))
+                .event(|e, m| e.name(m) == Some("d")),
+        );
+
+        assert_eq!(log.traces().len(), 6);
+        assert_eq!(names(&log.traces()[0]), vec!["a", "d"]);
+    }
+
+    #[test]
+    fn test_chaining() {
+        let first = Filter::new().event(|e, _| e.name(&Meta::default()) != Some("a"));
+        let second = Filter::new().event(|e, _| e.name(&Meta::default()) != Some("d"));
+
+        let mut log = Log::default();
+        let mut stream = second.stream(first.stream(load_example(&["xes", "book", "L1.xes"])));
+        log.consume(&mut stream).unwrap();
+
+        assert_eq!(names(&log.traces()[0]), vec!["e"]);
+    }
+
+    fn window_example(mode: WindowMode, start: &str, end: Option<&str>) -> Vec<usize> {
+        let window = TimeWindow::new(
+            mode,
+            Some(DateTime::parse_from_rfc3339(start).unwrap()),
+            end.map(|e| DateTime::parse_from_rfc3339(e).unwrap()),
+        );
+
+        let mut log = Log::default();
+        log.consume(&mut window.stream(load_example(&["xes", "book", "L1.xes"])))
+            .unwrap();
+
+        log.traces().iter().map(|t| t.events().len()).collect()
+    }
+
+    #[test]
+    fn test_time_window() {
+        let start = "2010-10-27T22:31:00+02:00";
+        let end = Some("2010-10-27T22:33:30+02:00");
+
+        assert_eq!(window_example(WindowMode::Contained, start, end), vec![3]);
+        assert_eq!(
+            window_example(WindowMode::Intersecting, start, end),
+            vec![3, 4, 4, 4, 4, 4]
+        );
+        assert_eq!(window_example(WindowMode::Trimmed, start, end), vec![3; 6]);
+
+        // same instant, different offset
+        let start = "2010-10-27T20:32:00Z";
+        let end = Some("2010-10-27T20:40:00Z");
+
+        assert!(window_example(WindowMode::Contained, start, end).is_empty());
+        assert_eq!(
+            window_example(WindowMode::Intersecting, start, end).len(),
+            6
+        );
+        assert_eq!(
+            window_example(WindowMode::Trimmed, start, end),
+            vec![2, 3, 3, 3, 3, 3]
+        );
+
+        // inclusive, open ended
+        let start = "2010-10-27T22:31:19.495+02:00";
+
+        assert_eq!(window_example(WindowMode::Contained, start, None).len(), 5);
+        assert_eq!(
+            window_example(WindowMode::Trimmed, start, None),
+            vec![3, 4, 4, 4, 3, 4]
+        );
+
+        let start = "2010-10-27T22:40:00+02:00";
+
+        assert!(window_example(WindowMode::Intersecting, start, None).is_empty());
+        assert!(window_example(WindowMode::Trimmed, start, None).is_empty());
+    }
+
+    #[test]
+    fn test_time_window_events() {
+        let at = |t: &str| {
+            Event::new().with_attribute(Attribute::new(
+                "time:timestamp",
+                AttributeType::Date(DateTime::parse_from_rfc3339(t).unwrap()),
+            ))
+        };
+        let log = Log::new()
+            .with_trace(Trace::new().with_event(Event::new()))
+            .with_event(at("2020-01-01T00:00:00Z"))
+            .with_event(at("2020-01-02T00:00:00Z"))
+            .with_event(Event::new());
+
+        let window = TimeWindow::new(
+            WindowMode::Contained,
+            None,
+            Some(DateTime::parse_from_rfc3339("2020-01-01T12:00:00+12:00").unwrap()),
+        );
+
+        let mut result = Log::default();
+        result
+            .consume(&mut window.stream(Buffer::from(log)))
+            .unwrap();
 
-pub struct Filter {/* TODO to be implemented */}
+        assert!(result.traces().is_empty());
+        assert_eq!(result.events().len(), 1);
+    }
+}
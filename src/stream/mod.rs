@@ -14,24 +14,38 @@
 // modules
 pub mod buffer;
 pub mod channel;
+pub mod defaults;
+pub mod expression;
 pub mod filter;
+pub mod report;
+pub mod sketch;
+pub mod spill;
 pub mod stats;
+pub mod variants;
 pub mod xes;
 pub mod xesext;
 pub mod xml_util;
 
 // standard library
+use std::borrow::Cow;
 use std::fmt::Debug;
 
 // third party
 
 // local
 use crate::error::{Error, Result};
-use crate::{Attribute, Classifier, Event, Extension, Global, Trace};
+use crate::stream::xesext::STANDARD;
+use crate::{Attribute, Classifier, Event, Extension, Global, Header, Log, Trace};
 
 /// Atomic unit of an extensible event stream
+///
+/// Further kinds of elements may be added, e.g. `Header` was introduced after the initial release.
+/// Hence, matching elements requires a wildcard arm outside of this crate.
+///
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Element {
+    Header(Header),
     Extension(Extension),
     Global(Global),
     Classifier(Classifier),
@@ -100,12 +114,7 @@ pub trait StreamSink {
 
 /// Stream sink that discards consumed contents
 pub fn consume<T: Stream>(stream: &mut T) -> Result<()> {
-    loop {
-        match stream.next()? {
-            None => break,
-            _ => (),
-        };
-    }
+    while stream.next()?.is_some() {}
 
     Ok(())
 }
@@ -164,6 +173,7 @@ impl<T: Stream, S: StreamSink> Stream for Duplicator<T, S> {
 /// State of an extensible event stream
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
 pub enum StreamState {
+    Header,
     Extension,
     Global,
     Classifier,
@@ -172,29 +182,124 @@ pub enum StreamState {
     Event,
 }
 
+impl From<&Element> for StreamState {
+    fn from(element: &Element) -> Self {
+        match element {
+            Element::Header(_) => StreamState::Header,
+            Element::Extension(_) => StreamState::Extension,
+            Element::Global(_) => StreamState::Global,
+            Element::Classifier(_) => StreamState::Classifier,
+            Element::Attribute(_) => StreamState::Attribute,
+            Element::Trace(_) => StreamState::Trace,
+            Element::Event(_) => StreamState::Event,
+        }
+    }
+}
+
 /// Store meta elements of an extensible event stream. Used by the observer.
 #[derive(Debug, Clone)]
 pub struct Meta {
     state: StreamState,
+    header: Option<Header>,
     extensions: Vec<Extension>,
     globals: Vec<Global>,
     classifiers: Vec<Classifier>,
     attributes: Vec<Attribute>,
+    // standard extensions declared with a prefix other than the suggested one
+    prefixes: Vec<(String, String)>,
 }
 
 impl Default for Meta {
     fn default() -> Self {
         Self {
-            state: StreamState::Extension,
+            state: StreamState::Header,
+            header: None,
             extensions: Vec::new(),
             globals: Vec::new(),
             classifiers: Vec::new(),
             attributes: Vec::new(),
+            prefixes: Vec::new(),
+        }
+    }
+}
+
+impl From<&Log> for Meta {
+    fn from(log: &Log) -> Self {
+        let mut meta = Meta::default();
+
+        for extension in log.extensions.iter() {
+            meta.declare(extension);
+        }
+
+        Meta {
+            header: log.header.clone(),
+            globals: log.globals.clone(),
+            classifiers: log.classifiers.clone(),
+            attributes: log.attributes.clone(),
+            ..meta
         }
     }
 }
 
 impl Meta {
+    /// State of the stream reached so far
+    pub fn state(&self) -> &StreamState {
+        &self.state
+    }
+
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
+
+    pub fn extensions(&self) -> &[Extension] {
+        &self.extensions
+    }
+
+    pub fn globals(&self) -> &[Global] {
+        &self.globals
+    }
+
+    pub fn classifiers(&self) -> &[Classifier] {
+        &self.classifiers
+    }
+
+    /// Look up a classifier by name
+    pub fn classifier(&self, name: &str) -> Option<&Classifier> {
+        self.classifiers.iter().find(|c| c.name() == name)
+    }
+
+    /// Log level attributes
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    /// Key of a standard extension attribute with respect to the declared extensions
+    ///
+    /// Standard extensions are identified by their URI, see `xesext::Registry`. If a log declares
+    /// one with a prefix other than the suggested one, the key is translated accordingly, e.g.
+    /// `concept:name` becomes `c:name`. Other keys are returned as they are.
+    ///
+    pub fn key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        if let Some(i) = key.find(':') {
+            if let Some((_, prefix)) = self.prefixes.iter().find(|(p, _)| *p == key[..i]) {
+                return Cow::Owned(format!("{}{}", prefix, &key[i..]));
+            }
+        }
+
+        Cow::Borrowed(key)
+    }
+
+    fn declare(&mut self, extension: &Extension) {
+        if let Some(definition) = STANDARD.by_uri(&extension.uri) {
+            if definition.prefix != extension.prefix {
+                self.prefixes
+                    .push((definition.prefix.clone(), extension.prefix.clone()));
+            }
+        }
+
+        self.extensions.push(extension.clone());
+    }
+
     /// Update meta cache by given element
     ///
     /// If the given element contains meta data a copy of it is cached. If the triggered state
@@ -205,30 +310,15 @@ impl Meta {
         let old_state = self.state.clone();
 
         // state transition
-        let new_state = match element {
-            Element::Extension(e) => {
-                let extension = e.clone();
-                self.extensions.push(extension);
-                StreamState::Extension
-            }
-            Element::Global(g) => {
-                self.globals.push(g.clone());
-                StreamState::Global
-            }
-            Element::Classifier(c) => {
-                let classifier = c.clone();
-                self.classifiers.push(classifier);
-
-                StreamState::Classifier
-            }
-            Element::Attribute(a) => {
-                let attribute = a.clone();
-                self.attributes.push(attribute);
-
-                StreamState::Attribute
-            }
-            Element::Trace(_trace) => StreamState::Trace,
-            Element::Event(_event) => StreamState::Event,
+        let new_state = StreamState::from(element);
+
+        match element {
+            Element::Header(h) => self.header = Some(h.clone()),
+            Element::Extension(e) => self.declare(e),
+            Element::Global(g) => self.globals.push(g.clone()),
+            Element::Classifier(c) => self.classifiers.push(c.clone()),
+            Element::Attribute(a) => self.attributes.push(a.clone()),
+            Element::Trace(_) | Element::Event(_) => (),
         };
 
         // check transition
@@ -324,6 +414,7 @@ impl<'a, I: Stream, H: Handler> Observer<I, H> {
         }
 
         let element = match element {
+            Element::Header(e) => Element::Header(e),
             Element::Extension(e) => Element::Extension(e),
             Element::Global(e) => Element::Global(e),
             Element::Classifier(e) => Element::Classifier(e),
@@ -340,22 +431,18 @@ impl<'a, I: Stream, H: Handler> Observer<I, H> {
 
                 let mut tmp: Vec<Event> = Vec::new();
 
-                loop {
-                    if let Some(event) = trace.events.pop() {
-                        let mut event = Some(event);
+                for event in std::mem::take(&mut trace.events) {
+                    let mut event = Some(event);
 
-                        for handler in self.handler.iter_mut() {
-                            event = match event {
-                                Some(event) => handler.event(event, true, &self.meta)?,
-                                None => None,
-                            }
+                    for handler in self.handler.iter_mut() {
+                        event = match event {
+                            Some(event) => handler.event(event, true, &self.meta)?,
+                            None => None,
                         }
+                    }
 
-                        if let Some(event) = event {
-                            tmp.push(event);
-                        }
-                    } else {
-                        break;
+                    if let Some(event) = event {
+                        tmp.push(event);
                     }
                 }
 
@@ -383,14 +470,10 @@ impl<'a, I: Stream, H: Handler> Observer<I, H> {
 
 impl<I: Stream, H: Handler> Stream for Observer<I, H> {
     fn next(&mut self) -> ResOpt {
-        loop {
-            if let Some(element) = self.stream.next()? {
-                let transition = self.meta.update(&element)?;
-                if let Some(element) = self.handle_element(element, transition)? {
-                    return Ok(Some(element));
-                }
-            } else {
-                break;
+        while let Some(element) = self.stream.next()? {
+            let transition = self.meta.update(&element)?;
+            if let Some(element) = self.handle_element(element, transition)? {
+                return Ok(Some(element));
             }
         }
 
@@ -409,14 +492,14 @@ mod tests {
     fn test_consume() {
         let mut buffer = buffer::tests::load_example(&["xes", "book", "L1.xes"]);
 
-        assert_eq!(buffer.len(), 19);
+        assert_eq!(buffer.len(), 20);
 
         consume(&mut buffer).unwrap();
 
         assert_eq!(buffer.len(), 0);
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, Default)]
     struct TestSink {
         ct_open: usize,
         ct_element: usize,
@@ -424,17 +507,6 @@ mod tests {
         ct_error: usize,
     }
 
-    impl Default for TestSink {
-        fn default() -> Self {
-            TestSink {
-                ct_open: 0,
-                ct_element: 0,
-                ct_close: 0,
-                ct_error: 0,
-            }
-        }
-    }
-
     impl StreamSink for TestSink {
         fn on_open(&mut self) -> Result<()> {
             self.ct_open += 1;
@@ -481,13 +553,13 @@ mod tests {
     #[test]
     fn test_sink_duplicator() {
         let param = [
-            ("book", "L1.xes", [1, 19, 1, 0]),
-            ("book", "L2.xes", [1, 26, 1, 0]),
-            ("book", "L3.xes", [1, 17, 1, 0]),
-            ("book", "L4.xes", [1, 160, 1, 0]),
-            ("book", "L5.xes", [1, 27, 1, 0]),
-            ("correct", "log_correct_attributes.xes", [1, 0, 1, 0]),
-            ("correct", "event_correct_attributes.xes", [1, 9, 1, 0]),
+            ("book", "L1.xes", [1, 20, 1, 0]),
+            ("book", "L2.xes", [1, 27, 1, 0]),
+            ("book", "L3.xes", [1, 18, 1, 0]),
+            ("book", "L4.xes", [1, 161, 1, 0]),
+            ("book", "L5.xes", [1, 28, 1, 0]),
+            ("correct", "log_correct_attributes.xes", [1, 1, 1, 0]),
+            ("correct", "event_correct_attributes.xes", [1, 10, 1, 0]),
         ];
 
         for (d, f, counts) in param.iter() {
@@ -495,11 +567,11 @@ mod tests {
         }
 
         let param = [
-            ("non_parsing", "boolean_incorrect_value.xes", [1, 5, 0, 1]),
-            ("non_parsing", "broken_xml.xes", [1, 18, 0, 1]),
-            ("non_parsing", "element_incorrect.xes", [1, 6, 0, 1]),
+            ("non_parsing", "boolean_incorrect_value.xes", [1, 6, 0, 1]),
+            ("non_parsing", "broken_xml.xes", [1, 19, 0, 1]),
+            ("non_parsing", "element_incorrect.xes", [1, 7, 0, 1]),
             ("non_parsing", "no_log.xes", [1, 0, 0, 1]),
-            ("non_parsing", "global_incorrect_scope.xes", [1, 1, 0, 1]),
+            ("non_parsing", "global_incorrect_scope.xes", [1, 2, 0, 1]),
         ];
 
         for (d, f, counts) in param.iter() {
@@ -598,6 +670,47 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Default)]
+    struct OrderHandler {
+        names: Vec<String>,
+    }
+
+    impl Handler for OrderHandler {
+        fn event(&mut self, event: Event, in_trace: bool, meta: &Meta) -> Result<Option<Event>> {
+            if in_trace {
+                self.names
+                    .push(event.name(meta).unwrap_or_default().to_string());
+            }
+
+            Ok(Some(event))
+        }
+    }
+
+    // Events of a trace are handled and forwarded in document order.
+    #[test]
+    fn test_observer_event_order() {
+        let path = expand_static(&["xes", "book", "L1.xes"]);
+        let expected = crate::Log::open(&path).unwrap();
+        let meta = Meta::from(&expected);
+        let names = |log: &crate::Log| -> Vec<String> {
+            log.traces()
+                .iter()
+                .flat_map(|t| t.events().iter())
+                .map(|e| e.name(&meta).unwrap_or_default().to_string())
+                .collect()
+        };
+
+        let mut observer = Observer::new(XesReader::from(open_buffered(&path)));
+        observer.register(OrderHandler::default());
+
+        let mut log = crate::Log::new();
+        log.consume(&mut observer).unwrap();
+
+        assert_eq!(names(&expected)[..3], ["a", "e", "d"]);
+        assert_eq!(observer.release().unwrap().names, names(&expected));
+        assert_eq!(names(&log), names(&expected));
+    }
+
     #[test]
     fn test_observer_filtering() {
         let param = [
@@ -644,7 +757,8 @@ mod tests {
 
             assert!(
                 consume(&mut observer).is_err(),
-                format!("expected state error: {:?}", n)
+                "expected state error: {:?}",
+                n
             )
         }
     }
@@ -0,0 +1,318 @@
+//! Machine readable statistics.
+//!
+//! Statistics report their results as flat records, each consisting of a section, a key within
+//! that section (possibly empty), an item the metric refers to (possibly empty, e.g. an attribute
+//! value), a metric and a value. This schema is the same for all statistics and serializes to JSON
+//! as well as CSV:
+//!
+//! ```json
+//! {"stats": "StreamStats", "records": [
+//!   {"section": "counts", "key": "", "item": "", "metric": "traces", "value": 6},
+//!   ...
+//! ]}
+//! ```
+//!
+//! ```csv
+//! stats,section,key,item,metric,value
+//! StreamStats,counts,,,traces,6
+//! ...
+//! ```
+//!
+//! Durations are reported in seconds, dates in RFC 3339 format.
+//!
+
+// standard library
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// third party
+use chrono::Duration;
+
+// local
+use crate::error::{Error, Result};
+
+/// Value of a record
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Integer(value as i64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<Duration> for Value {
+    fn from(value: Duration) -> Self {
+        match value.num_nanoseconds() {
+            Some(ns) => Value::Float(ns as f64 / 1e9),
+            None => Value::Float(value.num_milliseconds() as f64 / 1e3),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+/// A single result
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub section: String,
+    pub key: String,
+    pub item: String,
+    pub metric: String,
+    pub value: Value,
+}
+
+impl Record {
+    /// Create a new record
+    pub fn new<S: Into<String>, K: Into<String>, M: Into<String>, V: Into<Value>>(
+        section: S,
+        key: K,
+        metric: M,
+        value: V,
+    ) -> Self {
+        Record {
+            section: section.into(),
+            key: key.into(),
+            item: String::new(),
+            metric: metric.into(),
+            value: value.into(),
+        }
+    }
+
+    /// Set the item the metric refers to
+    pub fn with_item<I: Into<String>>(mut self, item: I) -> Self {
+        self.item = item.into();
+        self
+    }
+}
+
+/// Statistics that can be exported
+pub trait Report {
+    /// Name of the statistics
+    fn name(&self) -> &str;
+
+    /// Results as records
+    fn records(&self) -> Vec<Record>;
+
+    /// Write results as JSON
+    fn write_json(&self, writer: &mut dyn Write) -> Result<()> {
+        write!(
+            writer,
+            "{{\"stats\": {}, \"records\": [",
+            json_string(self.name())
+        )?;
+
+        for (i, record) in self.records().iter().enumerate() {
+            let value = match &record.value {
+                Value::Integer(value) => value.to_string(),
+                Value::Float(value) if value.is_finite() => format!("{:?}", value),
+                Value::Float(_) => String::from("null"),
+                Value::Text(value) => json_string(value),
+            };
+
+            write!(
+                writer,
+                "{}\n  {{\"section\": {}, \"key\": {}, \"item\": {}, \
+                 \"metric\": {}, \"value\": {}}}",
+                if i > 0 { "," } else { "" },
+                json_string(&record.section),
+                json_string(&record.key),
+                json_string(&record.item),
+                json_string(&record.metric),
+                value
+            )?;
+        }
+
+        writeln!(writer, "\n]}}")?;
+        Ok(())
+    }
+
+    /// Write results as CSV, including a header
+    fn write_csv(&self, writer: &mut dyn Write) -> Result<()> {
+        writeln!(writer, "stats,section,key,item,metric,value")?;
+
+        for record in self.records().iter() {
+            let value = match &record.value {
+                Value::Integer(value) => value.to_string(),
+                Value::Float(value) if value.is_finite() => format!("{:?}", value),
+                Value::Float(_) => String::new(),
+                Value::Text(value) => csv_field(value),
+            };
+
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                csv_field(self.name()),
+                csv_field(&record.section),
+                csv_field(&record.key),
+                csv_field(&record.item),
+                csv_field(&record.metric),
+                value
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Results as JSON string
+    fn to_json(&self) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        // writing to memory doesn't fail
+        self.write_json(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Results as CSV string
+    fn to_csv(&self) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        // writing to memory doesn't fail
+        self.write_csv(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    /// Write results to a file, the format is chosen by its extension (`json` or `csv`)
+    fn save(&self, path: &Path) -> Result<()> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let write = match extension.to_lowercase().as_str() {
+            "json" => Self::write_json,
+            "csv" => Self::write_csv,
+            _ => {
+                return Err(Error::FormatError(format!(
+                    "{:?}, expected `.json` or `.csv`",
+                    path
+                )))
+            }
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        write(self, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Example;
+
+    impl Report for Example {
+        fn name(&self) -> &str {
+            "Example"
+        }
+
+        fn records(&self) -> Vec<Record> {
+            vec![
+                Record::new("counts", "", "events", 3),
+                Record::new(
+                    "durations",
+                    "a, \"b\"",
+                    "mean",
+                    Duration::milliseconds(1500),
+                ),
+                Record::new("durations", "c", "mean", f64::NAN),
+                Record::new("values", "tab\there", "first", "x\ny"),
+                Record::new("values", "k", "count", 2).with_item("a,b"),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let expected = "{\"stats\": \"Example\", \"records\": [
+  {\"section\": \"counts\", \"key\": \"\", \"item\": \"\", \"metric\": \"events\", \"value\": 3},
+  {\"section\": \"durations\", \"key\": \"a, \\\"b\\\"\", \"item\": \"\", \
+\"metric\": \"mean\", \"value\": 1.5},
+  {\"section\": \"durations\", \"key\": \"c\", \"item\": \"\", \
+\"metric\": \"mean\", \"value\": null},
+  {\"section\": \"values\", \"key\": \"tab\\there\", \"item\": \"\", \
+\"metric\": \"first\", \"value\": \"x\\ny\"},
+  {\"section\": \"values\", \"key\": \"k\", \"item\": \"a,b\", \"metric\": \"count\", \"value\": 2}
+]}
+";
+        assert_eq!(Example.to_json(), expected);
+    }
+
+    #[test]
+    fn test_csv() {
+        let expected = "stats,section,key,item,metric,value
+Example,counts,,,events,3
+Example,durations,\"a, \"\"b\"\"\",,mean,1.5
+Example,durations,c,,mean,
+Example,values,tab\there,,first,\"x
+y\"
+Example,values,k,\"a,b\",count,2
+";
+        assert_eq!(Example.to_csv(), expected);
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = std::env::temp_dir();
+
+        for extension in ["json", "CSV"].iter() {
+            let path = dir.join(format!("promi-report-{}.{}", std::process::id(), extension));
+            Example.save(&path).unwrap();
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert!(content.contains("Example"));
+        }
+
+        assert!(matches!(
+            Example.save(&dir.join("promi-report.txt")),
+            Err(Error::FormatError(_))
+        ));
+    }
+}
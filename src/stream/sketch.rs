@@ -0,0 +1,457 @@
+//! Approximate statistics in constant memory.
+//!
+//! Exact statistics keep state that grows with the stream, e.g. a counter per distinct activity.
+//! For endless streams, the sketches of this module trade accuracy for a fixed memory footprint
+//! that only depends on their configuration:
+//!
+//! - `HyperLogLog` estimates the number of distinct items
+//! - `SpaceSaving` finds the most frequent items and bounds their counts
+//! - `TDigest` estimates quantiles of numeric values
+//!
+
+// standard library
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::f64::consts::PI;
+
+// third party
+
+// local
+
+/// Estimate the number of distinct items
+///
+/// Uses `2^precision` registers of one byte each, `precision` being clamped to 4..=16. The relative
+/// standard error of the estimate is `1.04 / sqrt(2^precision)`, e.g. about 1.6% for precision 12
+/// (4 KiB). Cardinalities small compared to the number of registers are estimated by linear
+/// counting, which is considerably more accurate.
+///
+/// Items are hashed by 64-bit FNV-1a over their UTF-8 bytes, finalized by the `fmix64` mixer of
+/// MurmurHash3. The hash depends neither on the platform nor on the Rust release, so sketches
+/// built by different processes can be merged.
+///
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    precision: u32,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Create an empty sketch
+    pub fn new(precision: u32) -> Self {
+        let precision = precision.clamp(4, 16);
+
+        HyperLogLog {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    /// Relative standard error of the estimate
+    pub fn error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    /// Add an item
+    pub fn insert(&mut self, item: &str) {
+        let hash = fmix64(fnv1a(item.as_bytes()));
+
+        let index = (hash >> (64 - self.precision)) as usize;
+        let rank = ((hash << self.precision).leading_zeros() + 1).min(64 - self.precision + 1);
+
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    /// Merge another sketch of the same precision
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        if self.precision == other.precision {
+            for (r, o) in self.registers.iter_mut().zip(other.registers.iter()) {
+                *r = (*r).max(*o);
+            }
+        }
+    }
+
+    /// Estimated number of distinct items
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum: f64 = self.registers.iter().map(|r| 2f64.powi(-(*r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|r| **r == 0).count();
+
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog::new(12)
+    }
+}
+
+/// 64-bit FNV-1a hash, see http://www.isthe.com/chongo/tech/comp/fnv/
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Finalizer of MurmurHash3 that lets every input bit affect every output bit
+///
+/// FNV-1a alone disperses short items poorly across the high bits that select a register.
+///
+fn fmix64(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Find the most frequent items
+///
+/// Keeps at most `capacity` counters. A reported count never underestimates the true count and
+/// overestimates it by at most the reported error, which is bounded by `n / capacity` for `n`
+/// items seen. Every item more frequent than `n / capacity` is guaranteed to be monitored. While
+/// there are no more distinct items than counters, counts are exact.
+///
+/// Monitored items are grouped by count in a stream summary, so that adding an item takes
+/// logarithmic time in the number of counters.
+///
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+    capacity: usize,
+    total: usize,
+    counters: HashMap<String, (usize, usize)>,
+    buckets: BTreeMap<usize, BTreeSet<String>>,
+}
+
+impl SpaceSaving {
+    /// Create an empty sketch with at least one counter
+    pub fn new(capacity: usize) -> Self {
+        SpaceSaving {
+            capacity: capacity.max(1),
+            total: 0,
+            counters: HashMap::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Number of items seen
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Upper bound of the overestimation of any count
+    pub fn error(&self) -> usize {
+        self.total / self.capacity
+    }
+
+    /// Add an item
+    pub fn insert(&mut self, item: &str) {
+        self.total += 1;
+
+        if let Some((count, _)) = self.counters.get_mut(item) {
+            let bucket = self.buckets.get_mut(count).unwrap();
+            let item = bucket.take(item).unwrap();
+            if bucket.is_empty() {
+                self.buckets.remove(count);
+            }
+
+            *count += 1;
+            self.buckets.entry(*count).or_default().insert(item);
+            return;
+        }
+
+        let (count, error) = if self.counters.len() < self.capacity {
+            (1, 0)
+        } else {
+            // replace the greatest of the least frequent items
+            let mut bucket = self.buckets.first_entry().unwrap();
+            let min = *bucket.key();
+            let evicted = bucket.get_mut().pop_last().unwrap();
+            if bucket.get().is_empty() {
+                bucket.remove();
+            }
+
+            self.counters.remove(&evicted);
+            (min + 1, min)
+        };
+
+        self.counters.insert(item.to_string(), (count, error));
+        self.buckets
+            .entry(count)
+            .or_default()
+            .insert(item.to_string());
+    }
+
+    /// Monitored items with their estimated count and maximum overestimation, sorted by descending
+    /// count and ascending item
+    pub fn top(&self) -> Vec<(String, usize, usize)> {
+        let mut top: Vec<(String, usize, usize)> = self
+            .counters
+            .iter()
+            .map(|(k, (c, e))| (k.clone(), *c, *e))
+            .collect();
+        top.sort_by(|(ka, ca, _), (kb, cb, _)| cb.cmp(ca).then_with(|| ka.cmp(kb)));
+        top
+    }
+}
+
+impl Default for SpaceSaving {
+    fn default() -> Self {
+        SpaceSaving::new(100)
+    }
+}
+
+/// Estimate quantiles of numeric values
+///
+/// Values are summarized by weighted centroids whose size is limited by the scale function
+/// `k(q) = compression / (2 pi) * asin(2q - 1)`, keeping centroids small towards the tails. Memory
+/// is bounded by about `6 * compression` values. Error bounds are empirical: the rank error of an
+/// estimate is roughly proportional to `q (1 - q) / compression`, i.e. extreme quantiles are the
+/// most accurate ones, and minimum and maximum are exact. As long as fewer than about
+/// `compression / 4` values were seen, all quantiles are exact.
+///
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<(f64, f64)>,
+    buffer: Vec<f64>,
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    /// Create an empty digest, `compression` is at least 10
+    pub fn new(compression: f64) -> Self {
+        TDigest {
+            compression: compression.max(10.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Number of values seen
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Add a value, NaN is ignored
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(value);
+
+        if self.buffer.len() as f64 >= 5.0 * self.compression {
+            self.centroids = self.merged();
+            self.buffer.clear();
+        }
+    }
+
+    /// Estimated quantile (0 to 1), `None` if no values were seen
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let centroids = self.merged();
+        let total = self.count as f64;
+        let target = q.clamp(0.0, 1.0) * total;
+
+        // the first and the last half centroid are interpolated towards the exact extremes
+        let (first, first_weight) = centroids[0];
+        if target < first_weight / 2.0 {
+            return Some(interpolate(self.min, first, target / (first_weight / 2.0)));
+        }
+
+        let mut cumulative = first_weight / 2.0;
+        for window in centroids.windows(2) {
+            let ((left, left_weight), (right, right_weight)) = (window[0], window[1]);
+            let step = (left_weight + right_weight) / 2.0;
+
+            if target < cumulative + step {
+                // singletons are exact, otherwise interpolate between centers
+                if left_weight == 1.0 && target < cumulative + 0.5 {
+                    return Some(left);
+                }
+                if right_weight == 1.0 && target >= cumulative + step - 0.5 {
+                    return Some(right);
+                }
+                return Some(interpolate(left, right, (target - cumulative) / step));
+            }
+
+            cumulative += step;
+        }
+
+        let (last, last_weight) = centroids[centroids.len() - 1];
+        let rest = (target - cumulative) / (last_weight / 2.0);
+        Some(interpolate(last, self.max, rest.min(1.0)))
+    }
+
+    /// Merge buffered values into centroids
+    fn merged(&self) -> Vec<(f64, f64)> {
+        let mut all: Vec<(f64, f64)> = self.centroids.clone();
+        all.extend(self.buffer.iter().map(|v| (*v, 1.0)));
+        all.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let total: f64 = all.iter().map(|(_, w)| w).sum();
+        let scale = |q: f64| self.compression / (2.0 * PI) * (2.0 * q.clamp(0.0, 1.0) - 1.0).asin();
+
+        let mut merged: Vec<(f64, f64)> = Vec::with_capacity(all.len());
+        let mut cumulative = 0.0;
+        let mut k_left = scale(0.0);
+
+        for (mean, weight) in all {
+            if let Some((m, w)) = merged.last_mut() {
+                if scale((cumulative + *w + weight) / total) - k_left <= 1.0 {
+                    *m += (mean - *m) * weight / (*w + weight);
+                    *w += weight;
+                    continue;
+                }
+
+                cumulative += *w;
+                k_left = scale(cumulative / total);
+            }
+
+            merged.push((mean, weight));
+        }
+
+        merged
+    }
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        TDigest::new(100.0)
+    }
+}
+
+fn interpolate(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hyper_log_log() {
+        let mut hll = HyperLogLog::new(12);
+        assert_eq!(hll.estimate(), 0.0);
+
+        for i in 0..100 {
+            hll.insert(&i.to_string());
+            hll.insert(&i.to_string());
+        }
+        let error = (hll.estimate() - 100.0).abs() / 100.0;
+        assert!(error < 3.0 * hll.error(), "{}", error);
+
+        for i in 0..100_000 {
+            hll.insert(&i.to_string());
+        }
+        let error = (hll.estimate() - 100_000.0).abs() / 100_000.0;
+        assert!(error < 3.0 * hll.error(), "{}", error);
+
+        let mut other = HyperLogLog::new(12);
+        for i in 100_000..200_000 {
+            other.insert(&i.to_string());
+        }
+        hll.merge(&other);
+        let error = (hll.estimate() - 200_000.0).abs() / 200_000.0;
+        assert!(error < 3.0 * hll.error(), "{}", error);
+    }
+
+    #[test]
+    fn test_hash() {
+        // reference values of FNV-1a
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+
+        assert_eq!(fmix64(0), 0);
+        assert_ne!(fmix64(fnv1a(b"a")) >> 52, fmix64(fnv1a(b"b")) >> 52);
+    }
+
+    #[test]
+    fn test_space_saving() {
+        let mut sketch = SpaceSaving::new(3);
+        let items = "aaaaabbbbcccdde";
+
+        for item in items.chars() {
+            sketch.insert(&item.to_string());
+        }
+
+        assert_eq!(sketch.total(), 15);
+        assert_eq!(sketch.error(), 5);
+
+        let top = sketch.top();
+        assert_eq!(top.len(), 3);
+        assert_eq!((top[0].0.as_str(), top[0].1), ("a", 5));
+
+        for (item, count, error) in top.iter() {
+            let exact = items.matches(item.as_str()).count();
+            assert!(count - error <= exact && exact <= *count, "{}", item);
+            assert!(*error <= sketch.error());
+        }
+
+        let mut sketch = SpaceSaving::new(10);
+        for item in items.chars() {
+            sketch.insert(&item.to_string());
+        }
+        let exact: Vec<usize> = sketch.top().iter().map(|(_, c, e)| c + e).collect();
+        assert_eq!(exact, vec![5, 4, 3, 2, 1]);
+        assert_eq!(
+            sketch.buckets.keys().collect::<Vec<_>>(),
+            vec![&1, &2, &3, &4, &5]
+        );
+    }
+
+    #[test]
+    fn test_t_digest() {
+        let mut digest = TDigest::new(100.0);
+        assert_eq!(digest.quantile(0.5), None);
+
+        for i in 1..=20 {
+            digest.insert(i as f64);
+        }
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(0.5), Some(11.0));
+        assert_eq!(digest.quantile(1.0), Some(20.0));
+
+        let mut digest = TDigest::new(100.0);
+        let n = 100_000;
+        for i in 0..n {
+            // deterministic permutation of 0..n
+            digest.insert(((i * 7919) % n) as f64);
+        }
+
+        assert_eq!(digest.count(), n);
+        assert!(digest.centroids.len() + digest.buffer.len() < 600);
+
+        for q in [0.001, 0.01, 0.25, 0.5, 0.75, 0.99, 0.999].iter() {
+            let estimate = digest.quantile(*q).unwrap();
+            let rank_error = (estimate / n as f64 - q).abs();
+            assert!(rank_error < 0.01, "{}: {}", q, estimate);
+        }
+        assert_eq!(digest.quantile(0.0), Some(0.0));
+        assert_eq!(digest.quantile(1.0), Some((n - 1) as f64));
+    }
+}
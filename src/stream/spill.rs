@@ -0,0 +1,646 @@
+//! Buffering event streams that exceed memory.
+//!
+//! A `SpillBuffer` keeps elements in memory up to a given budget for their serialized size. Further
+//! elements are written to a temporary file in a compact binary representation and read back in
+//! order. This allows multi-pass processing of logs that are larger than the available memory.
+//!
+
+// standard library
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// third party
+use chrono::{FixedOffset, TimeZone};
+
+// local
+use crate::error::{Error, Result};
+use crate::stream::{Element, ResOpt, Stream, StreamSink};
+use crate::{
+    Attribute, AttributeType, Classifier, DateTime, Event, Extension, Global, Header, Scope, Trace,
+};
+
+static SPILL_FILES: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+    writer: BufWriter<fs::File>,
+    reader: BufReader<fs::File>,
+    written: usize,
+    read: usize,
+}
+
+impl SpillFile {
+    fn create() -> Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "promi-spill-{}-{}.bin",
+            std::process::id(),
+            SPILL_FILES.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        // separate handles keep separate positions for writing and reading
+        let writer = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let reader = fs::File::open(&path)?;
+
+        Ok(SpillFile {
+            reader: BufReader::new(reader),
+            writer: BufWriter::new(writer),
+            path,
+            written: 0,
+            read: 0,
+        })
+    }
+
+    fn pending(&self) -> usize {
+        self.written - self.read
+    }
+
+    fn write(&mut self, record: &[u8]) -> Result<()> {
+        self.writer.write_all(record)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    fn read(&mut self, errors: &mut VecDeque<Error>) -> ResOpt {
+        self.writer.flush()?;
+
+        let mut decoder = Decoder {
+            reader: &mut self.reader,
+        };
+
+        let element = match decoder.u8()? {
+            RECORD_ELEMENT => Ok(Some(decoder.element()?)),
+            RECORD_ERROR => match errors.pop_front() {
+                Some(error) => Err(error),
+                None => Err(Error::StreamError(String::from("spilled error is missing"))),
+            },
+            other => Err(spill_error(other)),
+        };
+
+        self.read += 1;
+
+        // recycle file once all records are read
+        if self.read == self.written {
+            self.writer.get_ref().set_len(0)?;
+            self.writer.seek(SeekFrom::Start(0))?;
+            self.reader.seek(SeekFrom::Start(0))?;
+            self.written = 0;
+            self.read = 0;
+        }
+
+        element
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(error) = fs::remove_file(&self.path) {
+            warn!("failed to remove spill file {:?}: {:?}", self.path, error);
+        }
+    }
+}
+
+/// Buffer with a bounded serialized size in memory
+///
+/// Elements are kept in memory as long as their accumulated size in binary representation doesn't
+/// exceed the budget (in bytes). The budget is a serialized-size budget: the heap footprint of the
+/// buffered elements is larger, by a factor that depends on the number of attributes and the
+/// lengths of keys and values. Choose the budget accordingly. Once exceeded, elements are spilled
+/// to a temporary file until the buffer is drained, preserving their order. The file is removed
+/// once the buffer is dropped. Errors are kept in memory.
+///
+/// As drop-in replacement for `Buffer`, it acts as stream and as stream sink.
+///
+#[derive(Debug)]
+pub struct SpillBuffer {
+    budget: usize,
+    used: usize,
+    memory: VecDeque<(ResOpt, usize)>,
+    errors: VecDeque<Error>,
+    file: Option<SpillFile>,
+}
+
+impl SpillBuffer {
+    /// Create a new buffer with a budget for the serialized size of buffered elements in bytes
+    pub fn new(budget: usize) -> Self {
+        SpillBuffer {
+            budget,
+            used: 0,
+            memory: VecDeque::new(),
+            errors: VecDeque::new(),
+            file: None,
+        }
+    }
+
+    /// Number of buffered elements
+    pub fn len(&self) -> usize {
+        self.memory.len() + self.spilled()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of elements currently spilled to disk
+    pub fn spilled(&self) -> usize {
+        self.file.as_ref().map_or(0, |f| f.pending())
+    }
+
+    /// Append an element
+    pub fn push(&mut self, element: ResOpt) -> Result<()> {
+        let mut record = Vec::new();
+
+        match &element {
+            Ok(Some(element)) => {
+                record.push(RECORD_ELEMENT);
+                encode_element(&mut record, element);
+            }
+            Ok(None) => return Ok(()),
+            Err(_) => record.push(RECORD_ERROR),
+        };
+
+        if self.spilled() == 0 && self.used + record.len() <= self.budget {
+            self.used += record.len();
+            self.memory.push_back((element, record.len()));
+            return Ok(());
+        }
+
+        if self.file.is_none() {
+            self.file = Some(SpillFile::create()?);
+        }
+
+        if let Err(error) = element {
+            self.errors.push_back(error);
+        }
+
+        self.file.as_mut().unwrap().write(&record)
+    }
+}
+
+impl Stream for SpillBuffer {
+    fn next(&mut self) -> ResOpt {
+        if let Some((element, size)) = self.memory.pop_front() {
+            self.used -= size;
+            return element;
+        }
+
+        match &mut self.file {
+            Some(file) if file.pending() > 0 => file.read(&mut self.errors),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl StreamSink for SpillBuffer {
+    fn on_element(&mut self, element: Element) -> Result<()> {
+        self.push(Ok(Some(element)))
+    }
+
+    fn on_error(&mut self, error: Error) -> Result<()> {
+        self.push(Err(error))
+    }
+}
+
+const RECORD_ELEMENT: u8 = 0;
+const RECORD_ERROR: u8 = 1;
+
+fn spill_error(tag: u8) -> Error {
+    Error::StreamError(format!("corrupted spill file, unexpected tag {}", tag))
+}
+
+fn encode_usize(buffer: &mut Vec<u8>, value: usize) {
+    let mut value = value as u64;
+
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            buffer.push(byte);
+            break;
+        }
+
+        buffer.push(byte | 0x80);
+    }
+}
+
+fn encode_str(buffer: &mut Vec<u8>, value: &str) {
+    encode_usize(buffer, value.len());
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+fn encode_option_str(buffer: &mut Vec<u8>, value: Option<&str>) {
+    match value {
+        Some(value) => {
+            buffer.push(1);
+            encode_str(buffer, value);
+        }
+        None => buffer.push(0),
+    }
+}
+
+fn encode_scope(buffer: &mut Vec<u8>, scope: Option<&Scope>) {
+    buffer.push(match scope {
+        None => 0,
+        Some(Scope::Event) => 1,
+        Some(Scope::Trace) => 2,
+    });
+}
+
+fn encode_attributes(buffer: &mut Vec<u8>, attributes: &[Attribute]) {
+    encode_usize(buffer, attributes.len());
+
+    for attribute in attributes {
+        encode_str(buffer, &attribute.key);
+
+        match &attribute.value {
+            AttributeType::String(value) => {
+                buffer.push(0);
+                encode_str(buffer, value);
+            }
+            AttributeType::Date(value) => {
+                buffer.push(1);
+                buffer.extend_from_slice(&value.timestamp().to_le_bytes());
+                buffer.extend_from_slice(&value.timestamp_subsec_nanos().to_le_bytes());
+                buffer.extend_from_slice(&value.offset().local_minus_utc().to_le_bytes());
+            }
+            AttributeType::Int(value) => {
+                buffer.push(2);
+                buffer.extend_from_slice(&value.to_le_bytes());
+            }
+            AttributeType::Float(value) => {
+                buffer.push(3);
+                buffer.extend_from_slice(&value.to_le_bytes());
+            }
+            AttributeType::Boolean(value) => {
+                buffer.push(4);
+                buffer.push(*value as u8);
+            }
+            AttributeType::Id(value) => {
+                buffer.push(5);
+                encode_str(buffer, value);
+            }
+            AttributeType::List(values) => {
+                buffer.push(6);
+                encode_attributes(buffer, values);
+
+                match &attribute.values {
+                    Some(keys) => {
+                        encode_usize(buffer, keys.len() + 1);
+                        for key in keys.iter() {
+                            encode_option_str(buffer, key.as_deref());
+                        }
+                    }
+                    None => encode_usize(buffer, 0),
+                }
+            }
+        }
+
+        encode_attributes(buffer, &attribute.attributes);
+    }
+}
+
+fn encode_element(buffer: &mut Vec<u8>, element: &Element) {
+    match element {
+        Element::Header(header) => {
+            buffer.push(0);
+            encode_option_str(buffer, header.version.as_deref());
+            encode_usize(buffer, header.features.len());
+            for feature in header.features.iter() {
+                encode_str(buffer, feature);
+            }
+        }
+        Element::Extension(extension) => {
+            buffer.push(1);
+            encode_str(buffer, &extension.name);
+            encode_str(buffer, &extension.prefix);
+            encode_str(buffer, &extension.uri);
+        }
+        Element::Global(global) => {
+            buffer.push(2);
+            encode_scope(buffer, global.scope.as_ref());
+            encode_attributes(buffer, &global.attributes);
+        }
+        Element::Classifier(classifier) => {
+            buffer.push(3);
+            encode_str(buffer, &classifier.name);
+            encode_scope(buffer, classifier.scope.as_ref());
+            encode_str(buffer, &classifier.keys);
+        }
+        Element::Attribute(attribute) => {
+            buffer.push(4);
+            encode_attributes(buffer, std::slice::from_ref(attribute));
+        }
+        Element::Trace(trace) => {
+            buffer.push(5);
+            encode_attributes(buffer, &trace.attributes);
+            encode_usize(buffer, trace.events.len());
+            for event in trace.events.iter() {
+                encode_attributes(buffer, &event.attributes);
+            }
+        }
+        Element::Event(event) => {
+            buffer.push(6);
+            encode_attributes(buffer, &event.attributes);
+        }
+    }
+}
+
+struct Decoder<'a, R: Read> {
+    reader: &'a mut R,
+}
+
+impl<'a, R: Read> Decoder<'a, R> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+
+        loop {
+            if shift >= 64 {
+                return Err(Error::StreamError(String::from(
+                    "corrupted spill file, integer overflow",
+                )));
+            }
+
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value as usize);
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        // don't trust the length, allocate only for bytes that are actually there
+        let length = self.usize()?;
+        let mut bytes = Vec::new();
+        Read::take(&mut *self.reader, length as u64).read_to_end(&mut bytes)?;
+
+        if bytes.len() < length {
+            return Err(Error::StreamError(String::from(
+                "corrupted spill file, truncated string",
+            )));
+        }
+
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    fn option_string(&mut self) -> Result<Option<String>> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.string()?)),
+        }
+    }
+
+    fn scope(&mut self) -> Result<Option<Scope>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(Scope::Event)),
+            2 => Ok(Some(Scope::Trace)),
+            other => Err(spill_error(other)),
+        }
+    }
+
+    fn date(&mut self) -> Result<DateTime> {
+        let seconds = i64::from_le_bytes(self.bytes()?);
+        let nanos = u32::from_le_bytes(self.bytes()?);
+        let offset = i32::from_le_bytes(self.bytes()?);
+
+        FixedOffset::east_opt(offset)
+            .and_then(|o| o.timestamp_opt(seconds, nanos).single())
+            .ok_or_else(|| Error::StreamError(String::from("corrupted spill file, invalid date")))
+    }
+
+    fn attributes(&mut self) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+
+        for _ in 0..self.usize()? {
+            let key = self.string()?;
+            let mut values = None;
+            let value = match self.u8()? {
+                0 => AttributeType::String(self.string()?),
+                1 => AttributeType::Date(self.date()?),
+                2 => AttributeType::Int(i64::from_le_bytes(self.bytes()?)),
+                3 => AttributeType::Float(f64::from_le_bytes(self.bytes()?)),
+                4 => AttributeType::Boolean(self.u8()? != 0),
+                5 => AttributeType::Id(self.string()?),
+                6 => {
+                    let list = self.attributes()?;
+
+                    values = match self.usize()? {
+                        0 => None,
+                        n => Some(
+                            (1..n)
+                                .map(|_| self.option_string())
+                                .collect::<Result<Vec<_>>>()?,
+                        ),
+                    };
+
+                    AttributeType::List(list)
+                }
+                other => return Err(spill_error(other)),
+            };
+
+            attributes.push(Attribute {
+                key,
+                value,
+                attributes: self.attributes()?,
+                values,
+            });
+        }
+
+        Ok(attributes)
+    }
+
+    fn element(&mut self) -> Result<Element> {
+        Ok(match self.u8()? {
+            0 => {
+                let version = self.option_string()?;
+                let features = (0..self.usize()?)
+                    .map(|_| self.string())
+                    .collect::<Result<Vec<String>>>()?;
+
+                Element::Header(Header { version, features })
+            }
+            1 => Element::Extension(Extension {
+                name: self.string()?,
+                prefix: self.string()?,
+                uri: self.string()?,
+            }),
+            2 => Element::Global(Global {
+                scope: self.scope()?,
+                attributes: self.attributes()?,
+            }),
+            3 => {
+                let name = self.string()?;
+                let scope = self.scope()?;
+                Element::Classifier(Classifier::new(name, scope, self.string()?))
+            }
+            4 => match self.attributes()?.pop() {
+                Some(attribute) => Element::Attribute(attribute),
+                None => return Err(spill_error(4)),
+            },
+            5 => {
+                let attributes = self.attributes()?;
+                let events = (0..self.usize()?)
+                    .map(|_| {
+                        Ok(Event {
+                            attributes: self.attributes()?,
+                        })
+                    })
+                    .collect::<Result<Vec<Event>>>()?;
+
+                Element::Trace(Trace { attributes, events })
+            }
+            6 => Element::Event(Event {
+                attributes: self.attributes()?,
+            }),
+            other => return Err(spill_error(other)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::buffer::tests::load_example;
+    use crate::stream::buffer::Buffer;
+
+    fn snapshot<T: Stream>(stream: &mut T) -> Vec<String> {
+        let mut elements = Vec::new();
+
+        while let Some(element) = stream.next().unwrap() {
+            elements.push(format!("{:?}", element));
+        }
+
+        elements
+    }
+
+    #[test]
+    fn test_spill_buffer() {
+        let param = [
+            ("book", "L1.xes"),
+            ("book", "L4.xes"),
+            ("correct", "event_correct_attributes.xes"),
+            ("correct", "log_correct_attributes.xes"),
+            ("non_validating", "list_no_values.xes"),
+            ("non_validating", "list_two_values.xes"),
+        ];
+
+        for (d, f) in param.iter() {
+            let expected = snapshot(&mut load_example(&["xes", d, f]));
+
+            for budget in [0, 1024, usize::MAX].iter() {
+                let mut buffer = SpillBuffer::new(*budget);
+                buffer.consume(&mut load_example(&["xes", d, f])).unwrap();
+
+                if *budget == 0 {
+                    assert_eq!(buffer.spilled(), buffer.len());
+                } else if *budget == usize::MAX {
+                    assert_eq!(buffer.spilled(), 0);
+                }
+
+                assert_eq!(snapshot(&mut buffer), expected, "{} {}", f, budget);
+                assert!(buffer.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_spill_interleaved() {
+        let mut source = load_example(&["xes", "book", "L2.xes"]);
+        let total = source.len();
+        let mut buffer = SpillBuffer::new(512);
+        let mut sink = Buffer::default();
+        let mut spilled: usize = 0;
+
+        // alternate writing and reading
+        while let Some(element) = source.next().unwrap() {
+            buffer.push(Ok(Some(element))).unwrap();
+            spilled = spilled.max(buffer.spilled());
+
+            if buffer.len() % 3 == 0 {
+                sink.push(buffer.next());
+            }
+        }
+        buffer
+            .push(Err(Error::StreamError(String::from("end"))))
+            .unwrap();
+
+        while let Ok(Some(element)) = buffer.next() {
+            sink.push(Ok(Some(element)));
+        }
+
+        assert!(spilled > 0);
+        assert_eq!(sink.len(), total);
+        assert!(buffer.is_empty());
+        assert_eq!(
+            snapshot(&mut sink),
+            snapshot(&mut load_example(&["xes", "book", "L2.xes"]))
+        );
+    }
+
+    #[test]
+    fn test_decode_overflow() {
+        let mut bytes: &[u8] = &[0xff; 16];
+        let mut decoder = Decoder { reader: &mut bytes };
+
+        match decoder.usize() {
+            Err(Error::StreamError(message)) => assert!(message.contains("corrupted")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        // a length of 2^35 - 1 followed by three bytes
+        let mut bytes: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0x7f, b'a', b'b', b'c'];
+        let mut decoder = Decoder { reader: &mut bytes };
+
+        match decoder.string() {
+            Err(Error::StreamError(message)) => assert!(message.contains("truncated")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut bytes: &[u8] = &[3, b'a', b'b', b'c'];
+        let mut decoder = Decoder { reader: &mut bytes };
+        assert_eq!(decoder.string().unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_spill_errors() {
+        let mut buffer = SpillBuffer::new(0);
+        let event = Element::Event(Event::default());
+
+        buffer.push(Ok(Some(event.clone()))).unwrap();
+        buffer
+            .push(Err(Error::ValidationError(String::from("fnord"))))
+            .unwrap();
+        buffer.push(Ok(Some(event))).unwrap();
+
+        assert_eq!(buffer.spilled(), 3);
+        assert!(buffer.next().unwrap().is_some());
+        assert!(matches!(buffer.next(), Err(Error::ValidationError(_))));
+        assert!(buffer.next().unwrap().is_some());
+        assert!(buffer.next().unwrap().is_none());
+    }
+}
@@ -2,17 +2,32 @@
 //!
 
 // standard library
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
 // third party
+use chrono::Duration;
 
 // local
 use crate::error::Result;
+use crate::stream::report::{Record, Report, Value};
+use crate::stream::sketch::{HyperLogLog, SpaceSaving, TDigest};
+use crate::stream::variants::select_classifier;
+use crate::stream::xesext::ValueType;
 use crate::stream::{Element, Handler, Meta, ResOpt, Stream};
-use crate::{Event, Trace};
+use crate::{Attribute, AttributeType, Classifier, DateTime, Event, Identity, Trace};
 
-/// Count element types in an extensible event stream
+/// Count elements and attributes in an extensible event stream
+///
+/// Events are counted regardless whether they are part of a trace. Attributes are counted per
+/// level: `attributes` refers to log attributes, attributes nested in other attributes or lists are
+/// counted separately on all levels. Further, the time elapsed from the first until the last
+/// element and, if given a `xes::CountingReader`'s count, the input bytes are tracked.
+///
 #[derive(Debug)]
 pub struct Counter<T: Stream> {
     stream: T,
@@ -22,6 +37,12 @@ pub struct Counter<T: Stream> {
     pub attributes: usize,
     pub traces: usize,
     pub events: usize,
+    pub trace_attributes: usize,
+    pub event_attributes: usize,
+    pub nested_attributes: usize,
+    bytes: Option<Arc<AtomicUsize>>,
+    started: Option<Instant>,
+    finished: Option<Instant>,
 }
 
 impl<T: Stream> Counter<T> {
@@ -35,9 +56,21 @@ impl<T: Stream> Counter<T> {
             attributes: 0,
             traces: 0,
             events: 0,
+            trace_attributes: 0,
+            event_attributes: 0,
+            nested_attributes: 0,
+            bytes: None,
+            started: None,
+            finished: None,
         }
     }
 
+    /// Track input bytes, see `xes::CountingReader::bytes`
+    pub fn with_bytes(mut self, bytes: Arc<AtomicUsize>) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
     /// Counts as array
     pub fn counts(&self) -> [usize; 6] {
         [
@@ -49,20 +82,102 @@ impl<T: Stream> Counter<T> {
             self.events,
         ]
     }
+
+    /// Attribute counts per level (log, trace, event, nested) as array
+    pub fn attribute_counts(&self) -> [usize; 4] {
+        [
+            self.attributes,
+            self.trace_attributes,
+            self.event_attributes,
+            self.nested_attributes,
+        ]
+    }
+
+    /// Input bytes read so far, if tracked
+    pub fn bytes(&self) -> Option<usize> {
+        self.bytes.as_ref().map(|b| b.load(Ordering::Relaxed))
+    }
+
+    /// Time elapsed from the first element until the end of the stream, or until now if the
+    /// stream didn't end yet
+    pub fn elapsed(&self) -> std::time::Duration {
+        match (self.started, self.finished) {
+            (Some(started), Some(finished)) => finished - started,
+            (Some(started), None) => started.elapsed(),
+            _ => std::time::Duration::from_secs(0),
+        }
+    }
+
+    /// Events per second of elapsed time, `None` if no time elapsed yet
+    pub fn events_per_second(&self) -> Option<f64> {
+        self.per_second(self.events)
+    }
+
+    /// Input bytes per second of elapsed time, `None` if not tracked or no time elapsed yet
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        self.bytes().and_then(|b| self.per_second(b))
+    }
+
+    fn per_second(&self, count: usize) -> Option<f64> {
+        let seconds = self.elapsed().as_secs_f64();
+
+        if seconds > 0.0 {
+            Some(count as f64 / seconds)
+        } else {
+            None
+        }
+    }
+
+    fn count_event(&mut self, event: &Event) {
+        self.events += 1;
+        self.event_attributes += event.attributes.len();
+        self.nested_attributes += nested(&event.attributes);
+    }
+}
+
+/// Number of attributes nested in the given ones, recursively
+fn nested(attributes: &[Attribute]) -> usize {
+    attributes
+        .iter()
+        .map(|a| {
+            let items = match &a.value {
+                AttributeType::List(items) => items.as_slice(),
+                _ => &[],
+            };
+
+            a.attributes.len() + items.len() + nested(&a.attributes) + nested(items)
+        })
+        .sum()
 }
 
 impl<T: Stream> Stream for Counter<T> {
     fn next(&mut self) -> ResOpt {
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+        }
+
         let element = self.stream.next()?;
 
         match &element {
             Some(Element::Extension(_)) => self.extensions += 1,
             Some(Element::Global(_)) => self.globals += 1,
             Some(Element::Classifier(_)) => self.classifiers += 1,
-            Some(Element::Attribute(_)) => self.attributes += 1,
-            Some(Element::Trace(_)) => self.traces += 1,
-            Some(Element::Event(_)) => self.events += 1,
-            None => (),
+            Some(Element::Attribute(attribute)) => {
+                self.attributes += 1;
+                self.nested_attributes += nested(std::slice::from_ref(attribute));
+            }
+            Some(Element::Trace(trace)) => {
+                self.traces += 1;
+                self.trace_attributes += trace.attributes.len();
+                self.nested_attributes += nested(&trace.attributes);
+
+                for event in trace.events.iter() {
+                    self.count_event(event);
+                }
+            }
+            Some(Element::Event(event)) => self.count_event(event),
+            Some(Element::Header(_)) => (),
+            None => self.finished = Some(Instant::now()),
         }
 
         Ok(element)
@@ -78,30 +193,219 @@ impl<T: Stream> fmt::Display for Counter<T> {
         writeln!(f, "   attributes:  {}", self.attributes)?;
         writeln!(f, "   traces:      {}", self.traces)?;
         writeln!(f, "   events:      {}", self.events)?;
+        writeln!(
+            f,
+            "   attributes (trace / event / nested): {} / {} / {}",
+            self.trace_attributes, self.event_attributes, self.nested_attributes
+        )?;
+        writeln!(f, "   elapsed:     {:?}", self.elapsed())?;
+        if let Some(bytes) = self.bytes() {
+            writeln!(f, "   bytes:       {}", bytes)?;
+        }
         Ok(())
     }
 }
 
+impl<T: Stream> Report for Counter<T> {
+    fn name(&self) -> &str {
+        "Counter"
+    }
+
+    fn records(&self) -> Vec<Record> {
+        let metrics = [
+            "extensions",
+            "globals",
+            "classifiers",
+            "attributes",
+            "traces",
+            "events",
+        ];
+
+        let mut records: Vec<Record> = metrics
+            .iter()
+            .zip(self.counts().iter())
+            .map(|(metric, count)| Record::new("counts", "", *metric, *count))
+            .collect();
+
+        let levels = ["log", "trace", "event", "nested"];
+        records.extend(
+            levels
+                .iter()
+                .zip(self.attribute_counts().iter())
+                .map(|(level, count)| Record::new("attributes", *level, "count", *count)),
+        );
+
+        records.push(Record::new(
+            "throughput",
+            "",
+            "elapsed",
+            self.elapsed().as_secs_f64(),
+        ));
+        if let Some(bytes) = self.bytes() {
+            records.push(Record::new("throughput", "", "bytes", bytes));
+        }
+
+        records
+    }
+}
+
+/// Aggregation of a sample of numeric values
+///
+/// Percentiles are interpolated linearly between the closest ranks. The standard deviation is the
+/// one of the population.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary<T = f64> {
+    pub count: usize,
+    pub min: T,
+    pub max: T,
+    pub mean: T,
+    pub median: T,
+    pub std_dev: T,
+    pub p25: T,
+    pub p75: T,
+    pub p90: T,
+    pub p95: T,
+    pub p99: T,
+}
+
+impl<T> Summary<T> {
+    /// Convert all aggregated values
+    pub fn map<U, F: Fn(T) -> U>(self, f: F) -> Summary<U> {
+        Summary {
+            count: self.count,
+            min: f(self.min),
+            max: f(self.max),
+            mean: f(self.mean),
+            median: f(self.median),
+            std_dev: f(self.std_dev),
+            p25: f(self.p25),
+            p75: f(self.p75),
+            p90: f(self.p90),
+            p95: f(self.p95),
+            p99: f(self.p99),
+        }
+    }
+}
+
+impl<T: Clone + Into<Value>> Summary<T> {
+    /// Aggregated values as records of a section and key
+    pub fn records(&self, section: &str, key: &str) -> Vec<Record> {
+        let metrics = [
+            ("min", &self.min),
+            ("max", &self.max),
+            ("mean", &self.mean),
+            ("median", &self.median),
+            ("std_dev", &self.std_dev),
+            ("p25", &self.p25),
+            ("p75", &self.p75),
+            ("p90", &self.p90),
+            ("p95", &self.p95),
+            ("p99", &self.p99),
+        ];
+
+        let mut records = vec![Record::new(section, key, "count", self.count)];
+        records.extend(
+            metrics
+                .iter()
+                .map(|(metric, value)| Record::new(section, key, *metric, (*value).clone())),
+        );
+        records
+    }
+}
+
+impl Summary<Duration> {
+    /// Summarize durations with nanosecond precision, `None` if there are none
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        let values: Vec<f64> = durations.iter().map(|d| nanoseconds(*d)).collect();
+        Summary::new(&values).map(|s| s.map(|v| Duration::nanoseconds(v.round() as i64)))
+    }
+}
+
+impl fmt::Display for Summary<Duration> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {} / mean {} / median {} / max {} (std. dev. {})",
+            self.min, self.mean, self.median, self.max, self.std_dev
+        )
+    }
+}
+
+fn nanoseconds(duration: Duration) -> f64 {
+    match duration.num_nanoseconds() {
+        Some(ns) => ns as f64,
+        None => duration.num_milliseconds() as f64 * 1e6,
+    }
+}
+
+impl Summary {
+    /// Summarize values, `None` if there are none
+    pub fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let count = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / count as f64;
+        let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+
+        Some(Summary {
+            count,
+            min: sorted[0],
+            max: sorted[count - 1],
+            mean,
+            median: percentile(&sorted, 50.0),
+            std_dev: variance.sqrt(),
+            p25: percentile(&sorted, 25.0),
+            p75: percentile(&sorted, 75.0),
+            p90: percentile(&sorted, 90.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {} / mean {:.2} / median {} / max {} (std. dev. {:.2})",
+            self.min, self.mean, self.median, self.max, self.std_dev
+        )
+    }
+}
+
+/// Percentile (0 to 100) of sorted values
+///
+/// Values are interpolated linearly between the closest ranks.
+///
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+
+    let rank = p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
 /// Event and trace statistics
 ///
 /// Provides deeper inspection of an extensible event stream by looking into traces and providing
 /// aggregated statistics.
 ///
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct StreamStats {
     ct_trace: Vec<usize>,
     ct_event: usize,
 }
 
-impl Default for StreamStats {
-    fn default() -> Self {
-        Self {
-            ct_trace: Vec::new(),
-            ct_event: 0,
-        }
-    }
-}
-
 impl Handler for StreamStats {
     fn trace(&mut self, trace: Trace, _meta: &Meta) -> Result<Option<Trace>> {
         self.ct_trace.push(trace.events.len());
@@ -119,6 +423,29 @@ impl StreamStats {
     pub fn counts(&self) -> [usize; 2] {
         [self.ct_trace.len(), self.ct_event]
     }
+
+    /// Number of events per trace
+    pub fn trace_lengths(&self) -> &[usize] {
+        &self.ct_trace
+    }
+
+    /// Aggregated number of events per trace, `None` if there are no traces
+    pub fn trace_length_summary(&self) -> Option<Summary> {
+        let lengths: Vec<f64> = self.ct_trace.iter().map(|l| *l as f64).collect();
+        Summary::new(&lengths)
+    }
+
+    /// Percentile (0 to 100) of the number of events per trace
+    pub fn trace_length_percentile(&self, p: f64) -> Option<f64> {
+        let mut lengths: Vec<f64> = self.ct_trace.iter().map(|l| *l as f64).collect();
+        lengths.sort_by(|a, b| a.total_cmp(b));
+
+        if lengths.is_empty() {
+            None
+        } else {
+            Some(percentile(&lengths, p))
+        }
+    }
 }
 
 impl fmt::Display for StreamStats {
@@ -128,62 +455,1655 @@ impl fmt::Display for StreamStats {
         writeln!(f, "   traces:              {:?}", self.ct_trace.len())?;
         writeln!(f, "   events:              {:?}", self.ct_event)?;
         writeln!(f, "   events (standalone): {:?}", sa_events)?;
+        if let Some(summary) = self.trace_length_summary() {
+            writeln!(f, "   trace length:        {}", summary)?;
+        }
         Ok(())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::stream;
-    use crate::stream::buffer::tests::load_example;
+impl Report for StreamStats {
+    fn name(&self) -> &str {
+        "StreamStats"
+    }
 
-    #[test]
-    fn test_counter() {
-        let param = [
-            ("book", "L1.xes", [5, 2, 3, 3, 6, 0]),
-            ("book", "L2.xes", [5, 2, 3, 3, 13, 0]),
-            ("book", "L3.xes", [5, 2, 3, 3, 4, 0]),
-            ("book", "L4.xes", [5, 2, 3, 3, 147, 0]),
-            ("book", "L5.xes", [5, 2, 3, 3, 14, 0]),
-            ("correct", "log_correct_attributes.xes", [0, 0, 0, 0, 0, 0]),
-            (
-                "correct",
-                "event_correct_attributes.xes",
-                [2, 2, 1, 1, 1, 2],
-            ),
+    fn records(&self) -> Vec<Record> {
+        let sa_events = self.ct_event - self.ct_trace.iter().sum::<usize>();
+        let mut records = vec![
+            Record::new("counts", "", "traces", self.ct_trace.len()),
+            Record::new("counts", "", "events", self.ct_event),
+            Record::new("counts", "", "standalone_events", sa_events),
         ];
 
-        for (d, f, e) in param.iter() {
-            let mut stats = Counter::new(load_example(&["xes", d, f]));
+        if let Some(summary) = self.trace_length_summary() {
+            records.extend(summary.records("trace_length", ""));
+        }
 
-            stream::consume(&mut stats).unwrap();
+        records
+    }
+}
 
-            assert_eq!(stats.counts(), *e);
+/// Frequency table, sorted by descending count and ascending key
+pub type Frequencies = Vec<(String, usize)>;
+
+fn sorted(counts: &HashMap<String, usize>) -> Frequencies {
+    sort(counts.iter().map(|(k, v)| (k.clone(), *v)).collect())
+}
+
+/// Frequencies of identities, multi-key identities are joined by `+` for display
+fn labeled(counts: &HashMap<Identity, usize>) -> Frequencies {
+    sort(counts.iter().map(|(k, v)| (k.join("+"), *v)).collect())
+}
+
+fn sort(mut table: Frequencies) -> Frequencies {
+    table.sort_by(|(ka, ca), (kb, cb)| cb.cmp(ca).then_with(|| ka.cmp(kb)));
+    table
+}
+
+/// Summaries per key, sorted by key
+pub type Summaries = Vec<(String, Summary<Duration>)>;
+
+fn summarize(durations: &HashMap<String, Vec<Duration>>) -> Summaries {
+    let mut summaries: Summaries = durations
+        .iter()
+        .filter_map(|(k, v)| Summary::from_durations(v).map(|s| (k.clone(), s)))
+        .collect();
+    summaries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    summaries
+}
+
+/// Activity and resource statistics
+///
+/// Counts events per activity and per resource (`org:resource`), as well as the activities that
+/// start and end traces. Activities are identified by the given event classifier, looked up by name
+/// in the stream's meta data, or by `concept:name` if none is given. Identities of classifiers with
+/// several keys are reported joined by `+`. Events without activity or resource are not counted in
+/// the respective table.
+///
+#[derive(Debug, Default)]
+pub struct ActivityStats {
+    classifier_name: Option<String>,
+    classifier: Option<Classifier>,
+    activities: HashMap<Identity, usize>,
+    resources: HashMap<String, usize>,
+    start_activities: HashMap<Identity, usize>,
+    end_activities: HashMap<Identity, usize>,
+}
+
+impl ActivityStats {
+    /// Create activity statistics, identifying activities by `concept:name`
+    pub fn new() -> Self {
+        ActivityStats::default()
+    }
+
+    /// Identify activities by the event classifier of the given name
+    pub fn with_classifier<S: Into<String>>(mut self, name: S) -> Self {
+        self.classifier_name = Some(name.into());
+        self
+    }
+
+    /// Number of events per activity
+    pub fn activities(&self) -> Frequencies {
+        labeled(&self.activities)
+    }
+
+    /// Number of events per resource
+    pub fn resources(&self) -> Frequencies {
+        sorted(&self.resources)
+    }
+
+    /// Number of traces per first activity
+    pub fn start_activities(&self) -> Frequencies {
+        labeled(&self.start_activities)
+    }
+
+    /// Number of traces per last activity
+    pub fn end_activities(&self) -> Frequencies {
+        labeled(&self.end_activities)
+    }
+
+    fn activity(&mut self, event: &Event, meta: &Meta) -> Result<Option<Identity>> {
+        if self.classifier.is_none() {
+            self.classifier = Some(select_classifier(self.classifier_name.as_deref(), meta)?);
         }
+
+        Ok(self
+            .classifier
+            .as_ref()
+            .and_then(|c| c.event_identity(event, meta.globals())))
     }
+}
 
-    #[test]
-    fn test_stream_stats() {
-        let param = [
-            ("book", "L1.xes", [6, 23]),
-            ("book", "L2.xes", [13, 80]),
-            ("book", "L3.xes", [4, 39]),
-            ("book", "L4.xes", [147, 441]),
-            ("book", "L5.xes", [14, 92]),
-            ("correct", "log_correct_attributes.xes", [0, 0]),
-            ("correct", "event_correct_attributes.xes", [1, 4]),
+impl Handler for ActivityStats {
+    fn trace(&mut self, trace: Trace, meta: &Meta) -> Result<Option<Trace>> {
+        if let Some(first) = trace.events().first() {
+            if let Some(activity) = self.activity(first, meta)? {
+                *self.start_activities.entry(activity).or_insert(0) += 1;
+            }
+        }
+
+        if let Some(last) = trace.events().last() {
+            if let Some(activity) = self.activity(last, meta)? {
+                *self.end_activities.entry(activity).or_insert(0) += 1;
+            }
+        }
+
+        Ok(Some(trace))
+    }
+
+    fn event(&mut self, event: Event, _in_trace: bool, meta: &Meta) -> Result<Option<Event>> {
+        if let Some(activity) = self.activity(&event, meta)? {
+            *self.activities.entry(activity).or_insert(0) += 1;
+        }
+
+        if let Some(resource) = event.resource(meta) {
+            *self.resources.entry(resource.to_string()).or_insert(0) += 1;
+        }
+
+        Ok(Some(event))
+    }
+}
+
+impl fmt::Display for ActivityStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let tables = [
+            ("activities", self.activities()),
+            ("resources", self.resources()),
+            ("start activities", self.start_activities()),
+            ("end activities", self.end_activities()),
         ];
 
-        for (d, f, e) in param.iter() {
-            let buffer = load_example(&["xes", d, f]);
-            let mut observer = stream::Observer::new(buffer);
-            observer.register(StreamStats::default());
+        writeln!(f, "ActivityStats")?;
+        for (title, table) in tables.iter() {
+            writeln!(f, "   {}:", title)?;
+            for (key, count) in table.iter() {
+                writeln!(f, "      {:>8}  {}", count, key)?;
+            }
+        }
+        Ok(())
+    }
+}
 
-            stream::consume(&mut observer).unwrap();
+impl Report for ActivityStats {
+    fn name(&self) -> &str {
+        "ActivityStats"
+    }
 
-            let stats = observer.release().unwrap();
-            assert_eq!(stats.counts(), *e);
+    fn records(&self) -> Vec<Record> {
+        let tables = [
+            ("activities", self.activities()),
+            ("resources", self.resources()),
+            ("start_activities", self.start_activities()),
+            ("end_activities", self.end_activities()),
+        ];
+
+        tables
+            .iter()
+            .flat_map(|(section, table)| {
+                table
+                    .iter()
+                    .map(move |(key, count)| Record::new(*section, key.as_str(), "count", *count))
+            })
+            .collect()
+    }
+}
+
+/// Throughput, waiting and service times
+///
+/// Computed per trace from the `time:timestamp` of its events, events without timestamp are
+/// ignored. Activities are identified by the given event classifier, looked up by name in the
+/// stream's meta data, or by `concept:name` if none is given. Identities of classifiers with
+/// several keys are reported joined by `+`.
+///
+/// - throughput time: time between the earliest and the latest event of a trace
+/// - waiting time: time between consecutive events of a trace, attributed to the later event's
+///   activity
+/// - service time: time between a `start` and the next `complete` event of the same activity
+///   within a trace according to `lifecycle:transition`. Complete events without start are not
+///   taken into account, as the time since their preceding event includes waiting time. For the
+///   same reason, the classifier should not include `lifecycle:transition`.
+///
+/// Events outside of traces are not taken into account.
+///
+#[derive(Debug, Default)]
+pub struct DurationStats {
+    classifier_name: Option<String>,
+    classifier: Option<Classifier>,
+    throughput: Vec<Duration>,
+    waiting: HashMap<String, Vec<Duration>>,
+    service: HashMap<String, Vec<Duration>>,
+}
+
+impl DurationStats {
+    /// Create empty duration statistics
+    pub fn new() -> Self {
+        DurationStats::default()
+    }
+
+    /// Identify activities by the event classifier of the given name
+    pub fn with_classifier<S: Into<String>>(mut self, name: S) -> Self {
+        self.classifier_name = Some(name.into());
+        self
+    }
+
+    /// Throughput time per trace
+    pub fn throughput_times(&self) -> &[Duration] {
+        &self.throughput
+    }
+
+    /// Aggregated throughput time, `None` if there are no traces with timestamps
+    pub fn throughput_summary(&self) -> Option<Summary<Duration>> {
+        Summary::from_durations(&self.throughput)
+    }
+
+    /// Aggregated waiting time of the log
+    pub fn waiting_summary(&self) -> Option<Summary<Duration>> {
+        Summary::from_durations(&self.waiting.values().flatten().cloned().collect::<Vec<_>>())
+    }
+
+    /// Aggregated waiting time per activity
+    pub fn waiting_summaries(&self) -> Summaries {
+        summarize(&self.waiting)
+    }
+
+    /// Aggregated service time of the log
+    pub fn service_summary(&self) -> Option<Summary<Duration>> {
+        Summary::from_durations(&self.service.values().flatten().cloned().collect::<Vec<_>>())
+    }
+
+    /// Aggregated service time per activity
+    pub fn service_summaries(&self) -> Summaries {
+        summarize(&self.service)
+    }
+
+    fn activity(&mut self, event: &Event, meta: &Meta) -> Result<String> {
+        if self.classifier.is_none() {
+            self.classifier = Some(select_classifier(self.classifier_name.as_deref(), meta)?);
+        }
+
+        Ok(self
+            .classifier
+            .as_ref()
+            .and_then(|c| c.event_identity(event, meta.globals()))
+            .map(|identity| identity.join("+"))
+            .unwrap_or_default())
+    }
+}
+
+impl Handler for DurationStats {
+    fn trace(&mut self, trace: Trace, meta: &Meta) -> Result<Option<Trace>> {
+        let mut range: Option<(&DateTime, &DateTime)> = None;
+        let mut previous: Option<&DateTime> = None;
+        let mut started: HashMap<String, Vec<&DateTime>> = HashMap::new();
+
+        for event in trace.events() {
+            let timestamp = match event.timestamp(meta) {
+                Some(timestamp) => timestamp,
+                None => continue,
+            };
+            let activity = self.activity(event, meta)?;
+
+            if let Some(previous) = previous {
+                self.waiting
+                    .entry(activity.clone())
+                    .or_default()
+                    .push(*timestamp - *previous);
+            }
+
+            match event.transition(meta) {
+                Some("start") => started.entry(activity).or_default().push(timestamp),
+                Some("complete") => {
+                    if let Some(pending) = started.get_mut(&activity) {
+                        if !pending.is_empty() {
+                            let start = pending.remove(0);
+                            self.service
+                                .entry(activity)
+                                .or_default()
+                                .push(*timestamp - *start);
+                        }
+                    }
+                }
+                _ => (),
+            }
+
+            range = Some(match range {
+                Some((min, max)) => (min.min(timestamp), max.max(timestamp)),
+                None => (timestamp, timestamp),
+            });
+            previous = Some(timestamp);
+        }
+
+        if let Some((min, max)) = range {
+            self.throughput.push(*max - *min);
         }
+
+        Ok(Some(trace))
+    }
+}
+
+impl fmt::Display for DurationStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "DurationStats")?;
+        if let Some(summary) = self.throughput_summary() {
+            writeln!(f, "   throughput time: {}", summary)?;
+        }
+        if let Some(summary) = self.waiting_summary() {
+            writeln!(f, "   waiting time:    {}", summary)?;
+        }
+        if let Some(summary) = self.service_summary() {
+            writeln!(f, "   service time:    {}", summary)?;
+        }
+        for (activity, summary) in self.service_summaries().iter() {
+            writeln!(f, "      {}: {}", activity, summary)?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for DurationStats {
+    fn name(&self) -> &str {
+        "DurationStats"
+    }
+
+    fn records(&self) -> Vec<Record> {
+        let mut records = Vec::new();
+        let sections = [
+            ("throughput", self.throughput_summary(), Vec::new()),
+            ("waiting", self.waiting_summary(), self.waiting_summaries()),
+            ("service", self.service_summary(), self.service_summaries()),
+        ];
+
+        for (section, summary, summaries) in sections.iter() {
+            if let Some(summary) = summary {
+                records.extend(summary.records(section, ""));
+            }
+            for (activity, summary) in summaries.iter() {
+                records.extend(summary.records(section, activity));
+            }
+        }
+
+        records
+    }
+}
+
+/// Level at which attributes occur
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Level {
+    Log,
+    Trace,
+    Event,
+}
+
+/// Profile of a single attribute key
+///
+/// Values are distinguished by their string representation. For lists, only the list as a whole is
+/// taken into account. Memory is bounded: distinct values are estimated by a `HyperLogLog` and the
+/// most frequent values are found by a `SpaceSaving` sketch. `Date` and `Id` values are mostly
+/// unique, they are counted as distinct but not tracked as frequent values.
+///
+#[derive(Debug, Clone, Default)]
+pub struct AttributeProfile {
+    count: usize,
+    types: Vec<(ValueType, usize)>,
+    distinct: HyperLogLog,
+    values: SpaceSaving,
+    numeric: Option<(f64, f64, f64)>,
+    dates: Option<(DateTime, DateTime)>,
+}
+
+impl AttributeProfile {
+    /// Create an empty profile, monitoring up to `capacity` frequent values
+    pub fn new(capacity: usize) -> Self {
+        AttributeProfile {
+            values: SpaceSaving::new(capacity),
+            ..AttributeProfile::default()
+        }
+    }
+
+    fn update(&mut self, value: &AttributeType) {
+        self.count += 1;
+
+        let value_type = ValueType::from(value);
+        let label = value.to_string();
+        self.distinct.insert(&label);
+        if value_type != ValueType::Date && value_type != ValueType::Id {
+            self.values.insert(&label);
+        }
+
+        match self.types.iter_mut().find(|(t, _)| *t == value_type) {
+            Some((_, count)) => *count += 1,
+            None => self.types.push((value_type, 1)),
+        }
+
+        let number = match value {
+            AttributeType::Int(value) => Some(*value as f64),
+            AttributeType::Float(value) => Some(*value),
+            _ => None,
+        };
+
+        if let Some(number) = number {
+            self.numeric = Some(match self.numeric {
+                Some((min, max, sum)) => (min.min(number), max.max(number), sum + number),
+                None => (number, number, number),
+            });
+        }
+
+        if let AttributeType::Date(date) = value {
+            self.dates = Some(match self.dates {
+                Some((min, max)) => (min.min(*date), max.max(*date)),
+                None => (*date, *date),
+            });
+        }
+    }
+
+    /// Number of occurrences
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Observed value types and their number of occurrences, in order of first observation
+    pub fn types(&self) -> &[(ValueType, usize)] {
+        &self.types
+    }
+
+    /// Estimated number of distinct values
+    pub fn distinct(&self) -> usize {
+        self.distinct.estimate().round() as usize
+    }
+
+    /// The `n` most frequent values, except for `Date` and `Id` values
+    ///
+    /// Counts are exact as long as there are no more distinct values than monitored ones,
+    /// otherwise they are upper bounds, see `SpaceSaving`.
+    ///
+    pub fn top_values(&self, n: usize) -> Frequencies {
+        self.values
+            .top()
+            .into_iter()
+            .take(n)
+            .map(|(value, count, _)| (value, count))
+            .collect()
+    }
+
+    /// Minimum of `Int` and `Float` values
+    pub fn min(&self) -> Option<f64> {
+        self.numeric.map(|(min, _, _)| min)
+    }
+
+    /// Maximum of `Int` and `Float` values
+    pub fn max(&self) -> Option<f64> {
+        self.numeric.map(|(_, max, _)| max)
+    }
+
+    /// Mean of `Int` and `Float` values
+    pub fn mean(&self) -> Option<f64> {
+        let numbers: usize = self
+            .types
+            .iter()
+            .filter(|(t, _)| *t == ValueType::Int || *t == ValueType::Float)
+            .map(|(_, c)| c)
+            .sum();
+
+        self.numeric.map(|(_, _, sum)| sum / numbers as f64)
+    }
+
+    /// Earliest and latest `Date` value
+    pub fn date_range(&self) -> Option<(&DateTime, &DateTime)> {
+        self.dates.as_ref().map(|(min, max)| (min, max))
+    }
+}
+
+/// Number of most frequent values reported per attribute key
+const TOP_VALUES: usize = 10;
+
+/// Profile attribute keys at log, trace and event level
+///
+/// Only attributes directly attached to the log, traces and events are profiled, nested attributes
+/// and defaults of globals are not. Log attributes are profiled once the stream's payload begins,
+/// i.e. not at all for streams without traces and events. Memory grows with the number of
+/// attribute keys, but not with the number of values, see `AttributeProfile`.
+///
+#[derive(Debug)]
+pub struct AttributeProfiler {
+    capacity: usize,
+    elements: HashMap<Level, usize>,
+    profiles: HashMap<(Level, String), AttributeProfile>,
+}
+
+impl Default for AttributeProfiler {
+    fn default() -> Self {
+        AttributeProfiler {
+            capacity: 100,
+            elements: HashMap::new(),
+            profiles: HashMap::new(),
+        }
+    }
+}
+
+impl AttributeProfiler {
+    /// Create an empty profiler
+    pub fn new() -> Self {
+        AttributeProfiler::default()
+    }
+
+    /// Number of monitored values per attribute key, see `SpaceSaving`
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Number of profiled elements at a level
+    pub fn elements(&self, level: Level) -> usize {
+        self.elements.get(&level).cloned().unwrap_or(0)
+    }
+
+    /// Profile of an attribute key at a level
+    pub fn profile(&self, level: Level, key: &str) -> Option<&AttributeProfile> {
+        self.profiles.get(&(level, key.to_string()))
+    }
+
+    /// Profiles at a level, sorted by key
+    pub fn profiles(&self, level: Level) -> Vec<(&str, &AttributeProfile)> {
+        let mut profiles: Vec<(&str, &AttributeProfile)> = self
+            .profiles
+            .iter()
+            .filter(|((l, _), _)| *l == level)
+            .map(|((_, k), p)| (k.as_str(), p))
+            .collect();
+        profiles.sort_by_key(|(a, _)| *a);
+        profiles
+    }
+
+    /// Share of elements at a level that carry an attribute key
+    pub fn fill_rate(&self, level: Level, key: &str) -> f64 {
+        match (self.profile(level, key), self.elements(level)) {
+            (Some(profile), elements) if elements > 0 => profile.count as f64 / elements as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn update(&mut self, level: Level, attributes: &[Attribute]) {
+        *self.elements.entry(level).or_insert(0) += 1;
+
+        let capacity = self.capacity;
+        for attribute in attributes {
+            self.profiles
+                .entry((level, attribute.key.clone()))
+                .or_insert_with(|| AttributeProfile::new(capacity))
+                .update(&attribute.value);
+        }
+    }
+}
+
+impl Handler for AttributeProfiler {
+    fn meta(&mut self, meta: &Meta) {
+        self.update(Level::Log, meta.attributes());
+    }
+
+    fn trace(&mut self, trace: Trace, _meta: &Meta) -> Result<Option<Trace>> {
+        self.update(Level::Trace, trace.attributes());
+        Ok(Some(trace))
+    }
+
+    fn event(&mut self, event: Event, _in_trace: bool, _meta: &Meta) -> Result<Option<Event>> {
+        self.update(Level::Event, event.attributes());
+        Ok(Some(event))
+    }
+}
+
+impl fmt::Display for AttributeProfiler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "AttributeProfiler")?;
+        for level in [Level::Log, Level::Trace, Level::Event].iter() {
+            writeln!(f, "   {:?} ({}):", level, self.elements(*level))?;
+            for (key, profile) in self.profiles(*level) {
+                writeln!(
+                    f,
+                    "      {}: {:?}, fill rate {:.2}, {} distinct",
+                    key,
+                    profile.types(),
+                    self.fill_rate(*level, key),
+                    profile.distinct()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Report for AttributeProfiler {
+    fn name(&self) -> &str {
+        "AttributeProfiler"
+    }
+
+    fn records(&self) -> Vec<Record> {
+        let mut records = Vec::new();
+
+        for level in [Level::Log, Level::Trace, Level::Event].iter() {
+            let section = format!("{:?}", level).to_lowercase();
+            let values = format!("{}_values", section);
+
+            records.push(Record::new(
+                section.as_str(),
+                "",
+                "elements",
+                self.elements(*level),
+            ));
+
+            for (key, profile) in self.profiles(*level) {
+                let record = |metric: &str, value: Value| Record::new(&section, key, metric, value);
+
+                records.push(record("count", profile.count().into()));
+                records.push(record("fill_rate", self.fill_rate(*level, key).into()));
+                records.push(record("distinct", profile.distinct().into()));
+
+                for (value_type, count) in profile.types() {
+                    let metric = format!("type:{:?}", value_type).to_lowercase();
+                    records.push(record(&metric, (*count).into()));
+                }
+
+                let numeric = [
+                    ("min", profile.min()),
+                    ("max", profile.max()),
+                    ("mean", profile.mean()),
+                ];
+                for (metric, value) in numeric.iter() {
+                    if let Some(value) = value {
+                        records.push(record(metric, (*value).into()));
+                    }
+                }
+
+                if let Some((first, last)) = profile.date_range() {
+                    records.push(record("date_min", first.to_rfc3339().into()));
+                    records.push(record("date_max", last.to_rfc3339().into()));
+                }
+
+                for (value, count) in profile.top_values(TOP_VALUES) {
+                    records
+                        .push(Record::new(values.as_str(), key, "count", count).with_item(value));
+                }
+            }
+        }
+
+        records
+    }
+}
+
+/// Approximate statistics in constant memory
+///
+/// Approximate counterpart of `StreamStats`, `ActivityStats` and `DurationStats` for endless
+/// streams, see `stream::sketch` for error bounds:
+///
+/// - distinct cases, by the `concept:name` of traces or a case attribute of standalone events
+/// - distinct activities and the most frequent ones, by the `concept:name` of events
+/// - quantiles of throughput and waiting times of traces and of cases of standalone events
+///
+/// Standalone events are grouped into cases by the case attribute. A bounded number of cases is
+/// kept open at a time, see `with_open_cases`. Once exceeded, the least recently seen case is
+/// closed and its throughput time is recorded. Later events of a closed case start a new one.
+/// Throughput quantiles take cases that are still open into account.
+///
+#[derive(Debug, Default)]
+pub struct SketchStats {
+    case_key: Option<String>,
+    open: OpenCases,
+    cases: HyperLogLog,
+    activities: HyperLogLog,
+    heavy_hitters: SpaceSaving,
+    throughput: TDigest,
+    waiting: TDigest,
+}
+
+/// Earliest, latest and most recent timestamp of a case, and when the case was last seen
+#[derive(Debug)]
+struct OpenCase {
+    first: DateTime,
+    last: DateTime,
+    previous: DateTime,
+    seen: usize,
+}
+
+impl OpenCase {
+    fn throughput(&self) -> f64 {
+        nanoseconds(self.last - self.first)
+    }
+}
+
+/// Open cases of standalone events, indexed by when they were last seen
+#[derive(Debug)]
+struct OpenCases {
+    capacity: usize,
+    seen: usize,
+    cases: HashMap<String, OpenCase>,
+    recency: BTreeMap<usize, String>,
+}
+
+impl Default for OpenCases {
+    fn default() -> Self {
+        OpenCases {
+            capacity: 1000,
+            seen: 0,
+            cases: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+}
+
+impl OpenCases {
+    /// Add an event's timestamp to its case, returns the waiting time and a closed case, if any
+    fn update(&mut self, case: String, timestamp: &DateTime) -> (Option<f64>, Option<OpenCase>) {
+        self.seen += 1;
+
+        if let Some(open) = self.cases.get_mut(&case) {
+            let waiting = nanoseconds(*timestamp - open.previous);
+            open.first = open.first.min(*timestamp);
+            open.last = open.last.max(*timestamp);
+            open.previous = *timestamp;
+
+            let case = self.recency.remove(&open.seen).unwrap();
+            open.seen = self.seen;
+            self.recency.insert(self.seen, case);
+            return (Some(waiting), None);
+        }
+
+        let closed = if self.cases.len() >= self.capacity {
+            let (_, evicted) = self.recency.pop_first().unwrap();
+            self.cases.remove(&evicted)
+        } else {
+            None
+        };
+
+        let open = OpenCase {
+            first: *timestamp,
+            last: *timestamp,
+            previous: *timestamp,
+            seen: self.seen,
+        };
+        self.recency.insert(self.seen, case.clone());
+        self.cases.insert(case, open);
+
+        (None, closed)
+    }
+}
+
+impl SketchStats {
+    /// Create sketches with default configuration
+    pub fn new() -> Self {
+        SketchStats::default()
+    }
+
+    /// Identify cases of standalone events by an attribute
+    pub fn with_case_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.case_key = Some(key.into());
+        self
+    }
+
+    /// Number of cases of standalone events that are tracked at a time, at least one
+    pub fn with_open_cases(mut self, capacity: usize) -> Self {
+        self.open.capacity = capacity.max(1);
+        self
+    }
+
+    /// Precision of distinct counts, see `HyperLogLog`
+    pub fn with_precision(mut self, precision: u32) -> Self {
+        self.cases = HyperLogLog::new(precision);
+        self.activities = HyperLogLog::new(precision);
+        self
+    }
+
+    /// Number of monitored activities, see `SpaceSaving`
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.heavy_hitters = SpaceSaving::new(capacity);
+        self
+    }
+
+    /// Compression of duration quantiles, see `TDigest`
+    pub fn with_compression(mut self, compression: f64) -> Self {
+        self.throughput = TDigest::new(compression);
+        self.waiting = TDigest::new(compression);
+        self
+    }
+
+    /// Estimated number of distinct cases
+    pub fn distinct_cases(&self) -> f64 {
+        self.cases.estimate()
+    }
+
+    /// Estimated number of distinct activities
+    pub fn distinct_activities(&self) -> f64 {
+        self.activities.estimate()
+    }
+
+    /// Most frequent activities with estimated count and maximum overestimation
+    pub fn top_activities(&self) -> Vec<(String, usize, usize)> {
+        self.heavy_hitters.top()
+    }
+
+    /// Estimated quantile (0 to 1) of throughput times, including open cases
+    pub fn throughput_quantile(&self, q: f64) -> Option<Duration> {
+        let mut throughput = self.throughput.clone();
+        for open in self.open.cases.values() {
+            throughput.insert(open.throughput());
+        }
+
+        throughput
+            .quantile(q)
+            .map(|v| Duration::nanoseconds(v.round() as i64))
+    }
+
+    /// Estimated quantile (0 to 1) of waiting times
+    pub fn waiting_quantile(&self, q: f64) -> Option<Duration> {
+        self.waiting
+            .quantile(q)
+            .map(|v| Duration::nanoseconds(v.round() as i64))
+    }
+}
+
+impl Handler for SketchStats {
+    fn trace(&mut self, trace: Trace, meta: &Meta) -> Result<Option<Trace>> {
+        if let Some(name) = trace.name(meta) {
+            self.cases.insert(name);
+        }
+
+        let timestamps: Vec<&DateTime> = trace
+            .events()
+            .iter()
+            .filter_map(|e| e.timestamp(meta))
+            .collect();
+
+        for window in timestamps.windows(2) {
+            self.waiting.insert(nanoseconds(*window[1] - *window[0]));
+        }
+
+        if let (Some(first), Some(last)) = (timestamps.first(), timestamps.last()) {
+            self.throughput.insert(nanoseconds(**last - **first));
+        }
+
+        Ok(Some(trace))
+    }
+
+    fn event(&mut self, event: Event, in_trace: bool, meta: &Meta) -> Result<Option<Event>> {
+        if let Some(name) = event.name(meta) {
+            self.activities.insert(name);
+            self.heavy_hitters.insert(name);
+        }
+
+        if !in_trace {
+            let case = self
+                .case_key
+                .as_ref()
+                .and_then(|k| event.attribute_or_global(k, meta.globals()));
+
+            if let Some(case) = case {
+                let case = case.value.to_string();
+                self.cases.insert(&case);
+
+                if let Some(timestamp) = event.timestamp(meta) {
+                    let (waiting, closed) = self.open.update(case, timestamp);
+
+                    if let Some(waiting) = waiting {
+                        self.waiting.insert(waiting);
+                    }
+                    if let Some(closed) = closed {
+                        self.throughput.insert(closed.throughput());
+                    }
+                }
+            }
+        }
+
+        Ok(Some(event))
+    }
+}
+
+impl fmt::Display for SketchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "SketchStats")?;
+        writeln!(f, "   cases (distinct):      ~{:.0}", self.distinct_cases())?;
+        writeln!(
+            f,
+            "   activities (distinct): ~{:.0}",
+            self.distinct_activities()
+        )?;
+        if let Some(median) = self.throughput_quantile(0.5) {
+            writeln!(f, "   throughput time:       ~{} (median)", median)?;
+        }
+        if let Some(median) = self.waiting_quantile(0.5) {
+            writeln!(f, "   waiting time:          ~{} (median)", median)?;
+        }
+        for (activity, count, error) in self.top_activities().iter() {
+            writeln!(f, "      {:>8} (+/- {})  {}", count, error, activity)?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for SketchStats {
+    fn name(&self) -> &str {
+        "SketchStats"
+    }
+
+    fn records(&self) -> Vec<Record> {
+        let mut records = vec![
+            Record::new("distinct", "", "cases", self.distinct_cases()),
+            Record::new("distinct", "", "activities", self.distinct_activities()),
+        ];
+
+        let quantiles = [
+            ("p25", 0.25),
+            ("p50", 0.5),
+            ("p75", 0.75),
+            ("p90", 0.9),
+            ("p95", 0.95),
+            ("p99", 0.99),
+        ];
+        for (metric, q) in quantiles.iter() {
+            if let Some(quantile) = self.throughput_quantile(*q) {
+                records.push(Record::new("throughput", "", *metric, quantile));
+            }
+        }
+        for (metric, q) in quantiles.iter() {
+            if let Some(quantile) = self.waiting_quantile(*q) {
+                records.push(Record::new("waiting", "", *metric, quantile));
+            }
+        }
+
+        for (activity, count, error) in self.top_activities() {
+            records.push(Record::new(
+                "top_activities",
+                activity.as_str(),
+                "count",
+                count,
+            ));
+            records.push(Record::new("top_activities", activity, "error", error));
+        }
+
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream;
+    use crate::stream::buffer::tests::load_example;
+    use crate::stream::buffer::Buffer;
+    use crate::stream::StreamSink;
+    use crate::Log;
+
+    #[test]
+    fn test_counter() {
+        let param = [
+            ("book", "L1.xes", [5, 2, 3, 3, 6, 23]),
+            ("book", "L2.xes", [5, 2, 3, 3, 13, 80]),
+            ("book", "L3.xes", [5, 2, 3, 3, 4, 39]),
+            ("book", "L4.xes", [5, 2, 3, 3, 147, 441]),
+            ("book", "L5.xes", [5, 2, 3, 3, 14, 92]),
+            ("correct", "log_correct_attributes.xes", [0, 0, 0, 0, 0, 0]),
+            (
+                "correct",
+                "event_correct_attributes.xes",
+                [2, 2, 1, 1, 1, 4],
+            ),
+        ];
+
+        for (d, f, e) in param.iter() {
+            let mut stats = Counter::new(load_example(&["xes", d, f]));
+
+            stream::consume(&mut stats).unwrap();
+
+            assert_eq!(stats.counts(), *e);
+        }
+    }
+
+    #[test]
+    fn test_counter_attributes() {
+        let mut stats = Counter::new(load_example(&[
+            "xes",
+            "correct",
+            "event_correct_attributes.xes",
+        ]));
+        stream::consume(&mut stats).unwrap();
+
+        assert_eq!(stats.attribute_counts(), [1, 2, 8, 0]);
+
+        let string = |key: &str| Attribute::new(key, AttributeType::String(key.to_string()));
+        let list = Attribute::new(
+            "list",
+            AttributeType::List(vec![string("a"), string("b").with_attribute(string("c"))]),
+        );
+        let log = Log::new()
+            .with_attribute(string("d").with_attribute(string("e")))
+            .with_trace(
+                Trace::new()
+                    .with_attribute(list)
+                    .with_event(Event::new().with_attribute(string("f")))
+                    .with_event(Event::new()),
+            )
+            .with_event(Event::new().with_attribute(string("g").with_attribute(string("h"))));
+
+        let mut stats = Counter::new(Buffer::from(log));
+        stream::consume(&mut stats).unwrap();
+
+        assert_eq!(stats.counts()[4..], [1, 3]);
+        assert_eq!(stats.attribute_counts(), [1, 1, 2, 5]);
+    }
+
+    #[test]
+    fn test_counter_throughput() {
+        let path = crate::util::expand_static(&["xes", "book", "L1.xes"]);
+        let reader = crate::stream::xes::CountingReader::new(crate::util::open_buffered(&path));
+        let bytes = reader.bytes();
+
+        let mut stats = Counter::new(crate::stream::xes::XesReader::from(reader)).with_bytes(bytes);
+        assert_eq!(stats.bytes(), Some(0));
+        assert_eq!(stats.events_per_second(), None);
+        assert_eq!(stats.bytes_per_second(), None);
+
+        stream::consume(&mut stats).unwrap();
+
+        // reading stops at the closing log tag, i.e. before any trailing content
+        let content = std::fs::read_to_string(&path).unwrap();
+        let size = content.rfind("</log>").unwrap() + "</log>".len();
+        assert_eq!(stats.bytes(), Some(size));
+        assert!(stats.elapsed() > std::time::Duration::from_secs(0));
+        assert!(stats.events_per_second().unwrap() > 0.0);
+        assert!(stats.bytes_per_second().unwrap() > 0.0);
+
+        let elapsed = stats.elapsed();
+        assert_eq!(stats.elapsed(), elapsed);
+        assert!(stats
+            .records()
+            .contains(&Record::new("throughput", "", "bytes", size)));
+    }
+
+    #[test]
+    fn test_stream_stats() {
+        let param = [
+            ("book", "L1.xes", [6, 23]),
+            ("book", "L2.xes", [13, 80]),
+            ("book", "L3.xes", [4, 39]),
+            ("book", "L4.xes", [147, 441]),
+            ("book", "L5.xes", [14, 92]),
+            ("correct", "log_correct_attributes.xes", [0, 0]),
+            ("correct", "event_correct_attributes.xes", [1, 4]),
+        ];
+
+        for (d, f, e) in param.iter() {
+            let buffer = load_example(&["xes", d, f]);
+            let mut observer = stream::Observer::new(buffer);
+            observer.register(StreamStats::default());
+
+            stream::consume(&mut observer).unwrap();
+
+            let stats = observer.release().unwrap();
+            assert_eq!(stats.counts(), *e);
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(Summary::new(&[]), None);
+
+        let summary = Summary::new(&[3.0]).unwrap();
+        assert_eq!((summary.min, summary.median, summary.p99), (3.0, 3.0, 3.0));
+        assert_eq!(summary.std_dev, 0.0);
+
+        let summary = Summary::new(&[4.0, 1.0, 3.0, 2.0]).unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 4.0);
+        assert_eq!(summary.mean, 2.5);
+        assert_eq!(summary.median, 2.5);
+        assert_eq!(summary.p25, 1.75);
+        assert_eq!(summary.p75, 3.25);
+        assert!((summary.std_dev - 1.25_f64.sqrt()).abs() < 1e-12);
+
+        let sorted: Vec<f64> = (0..=10).map(|v| v as f64).collect();
+        assert_eq!(percentile(&sorted, 0.0), 0.0);
+        assert_eq!(percentile(&sorted, 35.0), 3.5);
+        assert_eq!(percentile(&sorted, 100.0), 10.0);
+        assert!(percentile(&[], 50.0).is_nan());
+    }
+
+    #[test]
+    fn test_trace_length_summary() {
+        let param = [
+            ("book", "L1.xes", Some((3.0, 4.0, 23.0 / 6.0, 4.0))),
+            ("book", "L3.xes", Some((6.0, 16.0, 39.0 / 4.0, 8.5))),
+            ("correct", "log_correct_attributes.xes", None),
+        ];
+
+        for (d, f, e) in param.iter() {
+            let buffer = load_example(&["xes", d, f]);
+            let mut observer = stream::Observer::new(buffer);
+            observer.register(StreamStats::default());
+
+            stream::consume(&mut observer).unwrap();
+
+            let stats = observer.release().unwrap();
+            let summary = stats
+                .trace_length_summary()
+                .map(|s| (s.min, s.max, s.mean, s.median));
+
+            assert_eq!(summary, *e, "{}", f);
+            assert_eq!(
+                stats.trace_length_percentile(50.0),
+                e.map(|(_, _, _, median)| median)
+            );
+            assert!(format!("{}", stats).contains("trace length") == e.is_some());
+        }
+    }
+
+    fn table(entries: &[(&str, usize)]) -> Frequencies {
+        entries.iter().map(|(k, c)| (k.to_string(), *c)).collect()
+    }
+
+    fn activity_example(stats: ActivityStats) -> Result<ActivityStats> {
+        let mut observer = stream::Observer::new(load_example(&["xes", "book", "L1.xes"]));
+        observer.register(stats);
+
+        stream::consume(&mut observer)?;
+
+        Ok(observer.release().unwrap())
+    }
+
+    #[test]
+    fn test_activity_stats() {
+        let stats = activity_example(ActivityStats::new()).unwrap();
+
+        assert_eq!(
+            stats.activities(),
+            table(&[("a", 6), ("d", 6), ("b", 5), ("c", 5), ("e", 1)])
+        );
+        assert_eq!(stats.resources(), table(&[("UNDEFINED", 23)]));
+        assert_eq!(stats.start_activities(), table(&[("a", 6)]));
+        assert_eq!(stats.end_activities(), table(&[("d", 6)]));
+        assert!(format!("{}", stats).contains("start activities"));
+
+        let stats =
+            activity_example(ActivityStats::new().with_classifier("MXMLLegacyClassifier")).unwrap();
+
+        assert_eq!(stats.activities()[0], ("a+complete".to_string(), 6));
+        assert_eq!(stats.end_activities(), table(&[("d+complete", 6)]));
+
+        assert!(activity_example(ActivityStats::new().with_classifier("fnord")).is_err());
+    }
+
+    #[test]
+    fn test_duration_stats() {
+        let mut observer = stream::Observer::new(load_example(&["xes", "book", "L1.xes"]));
+        observer.register(DurationStats::new());
+
+        stream::consume(&mut observer).unwrap();
+
+        let stats = observer.release().unwrap();
+        let minute = Duration::minutes(1);
+
+        assert_eq!(
+            stats.throughput_times(),
+            &[
+                minute * 2,
+                minute * 3,
+                minute * 3,
+                minute * 3,
+                minute * 3,
+                minute * 3
+            ]
+        );
+
+        let summary = stats.throughput_summary().unwrap();
+        assert_eq!((summary.min, summary.max), (minute * 2, minute * 3));
+        assert_eq!(summary.mean, Duration::seconds(170));
+        assert_eq!(summary.median, minute * 3);
+
+        let waiting = stats.waiting_summary().unwrap();
+        assert_eq!((waiting.count, waiting.mean), (17, minute));
+        assert_eq!(waiting.std_dev, Duration::zero());
+
+        // only complete events, hence no service times
+        assert!(stats.service_summaries().is_empty());
+        assert!(stats.service_summary().is_none());
+        assert!(format!("{}", stats).contains("throughput time"));
+    }
+
+    #[test]
+    fn test_service_time() {
+        let event = |name: &str, transition: &str, minute: u32| {
+            Event::new()
+                .with_attribute(Attribute::new(
+                    "concept:name",
+                    AttributeType::String(name.to_string()),
+                ))
+                .with_attribute(Attribute::new(
+                    "lifecycle:transition",
+                    AttributeType::String(transition.to_string()),
+                ))
+                .with_attribute(Attribute::new(
+                    "time:timestamp",
+                    AttributeType::Date(
+                        DateTime::parse_from_rfc3339(&format!("2020-01-01T10:{:02}:00Z", minute))
+                            .unwrap(),
+                    ),
+                ))
+        };
+        let log = Log::new().with_trace(
+            Trace::new()
+                .with_event(event("a", "start", 0))
+                .with_event(event("b", "start", 1))
+                .with_event(event("a", "complete", 5))
+                .with_event(event("b", "complete", 6))
+                .with_event(event("c", "complete", 10))
+                .with_event(event("a", "start", 12))
+                .with_event(event("a", "complete", 13))
+                .with_event(Event::new()),
+        );
+
+        let mut observer = stream::Observer::new(Buffer::from(log));
+        observer.register(DurationStats::new());
+
+        stream::consume(&mut observer).unwrap();
+
+        let stats = observer.release().unwrap();
+        let service: Vec<(String, Vec<i64>)> = stats
+            .service
+            .iter()
+            .map(|(a, d)| (a.clone(), d.iter().map(|d| d.num_minutes()).collect()))
+            .collect::<std::collections::BTreeMap<_, _>>()
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            service,
+            vec![("a".to_string(), vec![5, 1]), ("b".to_string(), vec![5])]
+        );
+        assert_eq!(stats.throughput_times(), &[Duration::minutes(13)]);
+        assert_eq!(
+            stats.service_summary().unwrap().median,
+            Duration::minutes(5)
+        );
+        assert_eq!(stats.waiting_summaries()[0].1.count, 3);
+    }
+
+    #[test]
+    fn test_duration_classifier() {
+        let stats = |durations: DurationStats| {
+            let mut observer = stream::Observer::new(load_example(&["xes", "book", "L1.xes"]));
+            observer.register(durations);
+            stream::consume(&mut observer).unwrap();
+            observer.release().unwrap()
+        };
+
+        let keys = |summaries: Summaries| -> Vec<String> {
+            summaries.into_iter().map(|(k, _)| k).collect()
+        };
+
+        let stats = stats(DurationStats::new().with_classifier("MXMLLegacyClassifier"));
+        assert_eq!(
+            keys(stats.waiting_summaries()),
+            vec!["b+complete", "c+complete", "d+complete", "e+complete"]
+        );
+
+        let mut observer = stream::Observer::new(load_example(&["xes", "book", "L1.xes"]));
+        observer.register(DurationStats::new().with_classifier("fnord"));
+        assert!(stream::consume(&mut observer).is_err());
+    }
+
+    #[test]
+    fn test_throughput_unordered() {
+        let event = |minute: u32| {
+            Event::new().with_attribute(Attribute::new(
+                "time:timestamp",
+                AttributeType::Date(
+                    DateTime::parse_from_rfc3339(&format!("2020-01-01T10:{:02}:00Z", minute))
+                        .unwrap(),
+                ),
+            ))
+        };
+        let log = Log::new().with_trace(
+            Trace::new()
+                .with_event(event(5))
+                .with_event(event(0))
+                .with_event(event(9))
+                .with_event(event(7)),
+        );
+
+        let mut observer = stream::Observer::new(Buffer::from(log));
+        observer.register(DurationStats::new());
+
+        stream::consume(&mut observer).unwrap();
+
+        let stats = observer.release().unwrap();
+        assert_eq!(stats.throughput_times(), &[Duration::minutes(9)]);
+    }
+
+    #[test]
+    fn test_attribute_profiler() {
+        let mut observer = stream::Observer::new(load_example(&["xes", "book", "L1.xes"]));
+        observer.register(AttributeProfiler::new());
+
+        stream::consume(&mut observer).unwrap();
+
+        let profiler = observer.release().unwrap();
+
+        assert_eq!(profiler.elements(Level::Log), 1);
+        assert_eq!(profiler.elements(Level::Trace), 6);
+        assert_eq!(profiler.elements(Level::Event), 23);
+        assert_eq!(profiler.profiles(Level::Log).len(), 3);
+
+        let name = profiler.profile(Level::Event, "concept:name").unwrap();
+        assert_eq!(name.types(), &[(ValueType::String, 23)]);
+        assert_eq!(name.distinct(), 5);
+        assert_eq!(name.top_values(2), table(&[("a", 6), ("d", 6)]));
+        assert_eq!(name.min(), None);
+        assert_eq!(profiler.fill_rate(Level::Event, "concept:name"), 1.0);
+        assert_eq!(profiler.fill_rate(Level::Event, "fnord"), 0.0);
+
+        let timestamp = profiler.profile(Level::Event, "time:timestamp").unwrap();
+        let (first, last) = timestamp.date_range().unwrap();
+        assert_eq!(first.to_rfc3339(), "2010-10-27T22:31:19.308+02:00");
+        assert_eq!(last.to_rfc3339(), "2010-10-27T22:34:19.495+02:00");
+        assert_eq!(timestamp.distinct(), 8);
+        assert!(timestamp.top_values(1).is_empty());
+        assert!(format!("{}", profiler).contains("concept:name"));
+    }
+
+    #[test]
+    fn test_attribute_profile() {
+        let mut profile = AttributeProfile::default();
+        let values = [
+            AttributeType::Int(4),
+            AttributeType::Float(-1.5),
+            AttributeType::String("n/a".to_string()),
+            AttributeType::Int(4),
+        ];
+
+        for value in values.iter() {
+            profile.update(value);
+        }
+
+        assert_eq!(profile.count(), 4);
+        assert_eq!(
+            profile.types(),
+            &[
+                (ValueType::Int, 2),
+                (ValueType::Float, 1),
+                (ValueType::String, 1)
+            ]
+        );
+        assert_eq!(profile.distinct(), 3);
+        assert_eq!(profile.top_values(1), table(&[("4", 2)]));
+        assert_eq!(profile.min(), Some(-1.5));
+        assert_eq!(profile.max(), Some(4.0));
+        assert_eq!(profile.mean(), Some(6.5 / 3.0));
+        assert!(profile.date_range().is_none());
+    }
+
+    #[test]
+    fn test_attribute_profile_bounded() {
+        let mut profile = AttributeProfile::new(4);
+
+        for i in 0..1000 {
+            profile.update(&AttributeType::Int(i % 100));
+            profile.update(&AttributeType::Int(-1));
+        }
+
+        assert_eq!(profile.values.top().len(), 4);
+        assert_eq!(profile.top_values(1), table(&[("-1", 1000)]));
+        assert!((profile.distinct() as i64 - 101).abs() <= 3);
+    }
+
+    fn observe<H: Handler>(path: &[&str], handler: H) -> H {
+        let mut observer = stream::Observer::new(load_example(path));
+        observer.register(handler);
+
+        stream::consume(&mut observer).unwrap();
+
+        observer.release().unwrap()
+    }
+
+    #[test]
+    fn test_sketch_stats() {
+        let files = [
+            "L1.xes",
+            "L2.xes",
+            "L3.xes",
+            "L4.xes",
+            "L5.xes",
+            "bigger-example.xes",
+        ];
+
+        for f in files.iter() {
+            let path = ["xes", "book", f];
+            let sketch = observe(&path, SketchStats::new().with_capacity(4));
+            let activities = observe(&path, ActivityStats::new());
+            let durations = observe(&path, DurationStats::new());
+            let mut log = Log::default();
+            log.consume(&mut load_example(&path)).unwrap();
+
+            // distinct counts within three standard errors
+            let meta = Meta::from(&log);
+            let cases: std::collections::HashSet<&str> =
+                log.traces().iter().filter_map(|t| t.name(&meta)).collect();
+            let error = 3.0 * HyperLogLog::default().error();
+            let distinct = [
+                (sketch.distinct_cases(), cases.len()),
+                (sketch.distinct_activities(), activities.activities().len()),
+            ];
+
+            for (estimate, exact) in distinct.iter() {
+                let exact = *exact as f64;
+                assert!(
+                    (estimate - exact).abs() <= error * exact,
+                    "{}: {}",
+                    f,
+                    exact
+                );
+            }
+
+            // heavy hitters never underestimate, overestimate at most by the error
+            let exact: HashMap<String, usize> = activities.activities().into_iter().collect();
+            let bound = activities
+                .activities()
+                .iter()
+                .map(|(_, c)| c)
+                .sum::<usize>()
+                / 4;
+
+            for (activity, count, error) in sketch.top_activities() {
+                let exact = exact.get(&activity).cloned().unwrap_or(0);
+                assert!(
+                    count - error <= exact && exact <= count,
+                    "{}: {}",
+                    f,
+                    activity
+                );
+                assert!(error <= bound);
+            }
+            for (activity, count) in exact.iter().filter(|(_, c)| **c > bound) {
+                assert!(
+                    sketch
+                        .top_activities()
+                        .iter()
+                        .any(|(a, _, _)| a == activity),
+                    "{}: {} ({})",
+                    f,
+                    activity,
+                    count
+                );
+            }
+
+            // quantiles within a rank tolerance of the exact values
+            let mut exact: Vec<f64> = durations
+                .throughput_times()
+                .iter()
+                .map(|d| nanoseconds(*d))
+                .collect();
+            exact.sort_by(|a, b| a.total_cmp(b));
+            let tolerance = (2.0 / exact.len() as f64).max(0.01) * 100.0;
+
+            for q in [0.0, 0.1, 0.5, 0.9, 1.0].iter() {
+                let estimate = nanoseconds(sketch.throughput_quantile(*q).unwrap());
+                let lower = percentile(&exact, q * 100.0 - tolerance);
+                let upper = percentile(&exact, q * 100.0 + tolerance);
+
+                assert!(lower <= estimate && estimate <= upper, "{}: {}", f, q);
+            }
+
+            assert_eq!(
+                sketch.waiting_quantile(1.0),
+                durations.waiting_summary().map(|s| s.max)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sketch_standalone_events() {
+        let event = |case: &str, name: &str, minute: u32| {
+            Event::new()
+                .with_attribute(Attribute::new(
+                    "case",
+                    AttributeType::String(case.to_string()),
+                ))
+                .with_attribute(Attribute::new(
+                    "concept:name",
+                    AttributeType::String(name.to_string()),
+                ))
+                .with_attribute(Attribute::new(
+                    "time:timestamp",
+                    AttributeType::Date(
+                        DateTime::parse_from_rfc3339(&format!("2020-01-01T10:{:02}:00Z", minute))
+                            .unwrap(),
+                    ),
+                ))
+        };
+        let log = || {
+            Log::new()
+                .with_event(event("1", "a", 0))
+                .with_event(event("2", "a", 1))
+                .with_event(event("1", "b", 4))
+                .with_event(event("2", "b", 3))
+                .with_event(event("2", "c", 9))
+        };
+        let stats = |sketch: SketchStats| {
+            let mut observer = stream::Observer::new(Buffer::from(log()));
+            observer.register(sketch.with_case_key("case"));
+            stream::consume(&mut observer).unwrap();
+            observer.release().unwrap()
+        };
+
+        let sketch = stats(SketchStats::new());
+        assert_eq!(sketch.distinct_cases().round(), 2.0);
+        assert_eq!(sketch.distinct_activities().round(), 3.0);
+        assert_eq!(sketch.top_activities()[0], ("a".to_string(), 2, 0));
+        assert_eq!(sketch.throughput_quantile(0.0), Some(Duration::minutes(4)));
+        assert_eq!(sketch.throughput_quantile(1.0), Some(Duration::minutes(8)));
+        assert_eq!(sketch.waiting_quantile(0.0), Some(Duration::minutes(2)));
+        assert_eq!(sketch.waiting_quantile(1.0), Some(Duration::minutes(6)));
+
+        // a single open case, each change of cases closes the previous one
+        let sketch = stats(SketchStats::new().with_open_cases(1));
+        assert_eq!(sketch.open.cases.len(), 1);
+        assert_eq!(sketch.throughput.count(), 3);
+        assert_eq!(sketch.throughput_quantile(1.0), Some(Duration::minutes(6)));
+        assert_eq!(sketch.waiting.count(), 1);
+
+        // events without case are not grouped
+        let mut observer = stream::Observer::new(Buffer::from(log()));
+        observer.register(SketchStats::new());
+        stream::consume(&mut observer).unwrap();
+        assert_eq!(observer.release().unwrap().throughput_quantile(0.5), None);
+    }
+
+    #[test]
+    fn test_open_cases() {
+        let timestamp = |minute: u32| {
+            DateTime::parse_from_rfc3339(&format!("2020-01-01T10:{:02}:00Z", minute)).unwrap()
+        };
+        let mut open = OpenCases {
+            capacity: 2,
+            ..OpenCases::default()
+        };
+
+        assert!(open.update("1".to_string(), &timestamp(0)).1.is_none());
+        assert!(open.update("2".to_string(), &timestamp(1)).1.is_none());
+        assert_eq!(open.update("1".to_string(), &timestamp(3)).0, Some(18e10));
+
+        // the least recently seen case is closed
+        let (waiting, closed) = open.update("3".to_string(), &timestamp(4));
+        assert_eq!(waiting, None);
+        assert_eq!(closed.unwrap().first, timestamp(1));
+        assert!(open.update("1".to_string(), &timestamp(5)).0.is_some());
+
+        let (_, closed) = open.update("2".to_string(), &timestamp(6));
+        assert_eq!(closed.unwrap().throughput(), 0.0);
+        assert_eq!(open.cases.len(), open.recency.len());
+        assert_eq!(open.recency.values().collect::<Vec<_>>(), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_reports() {
+        let path = ["xes", "book", "L1.xes"];
+        let mut counter = Counter::new(load_example(&path));
+        stream::consume(&mut counter).unwrap();
+
+        assert_eq!(
+            counter.records()[4],
+            Record::new("counts", "", "traces", 6_usize)
+        );
+        assert!(counter
+            .to_csv()
+            .starts_with("stats,section,key,item,metric,value\nCounter,counts,,,extensions,5\n"));
+
+        let stats = observe(&path, StreamStats::default());
+        let records = stats.records();
+        assert_eq!(records.len(), 3 + 11);
+        assert_eq!(
+            records[2],
+            Record::new("counts", "", "standalone_events", 0_usize)
+        );
+        assert_eq!(
+            records[3 + 4],
+            Record::new("trace_length", "", "median", 4.0)
+        );
+
+        let activities = observe(&path, ActivityStats::new());
+        assert!(activities.records().contains(&Record::new(
+            "start_activities",
+            "a",
+            "count",
+            6_usize
+        )));
+
+        let durations = observe(&path, DurationStats::new());
+        assert!(durations
+            .records()
+            .contains(&Record::new("throughput", "", "max", 180.0)));
+        assert!(durations
+            .records()
+            .contains(&Record::new("waiting", "e", "mean", 60.0)));
+
+        let profiler = observe(&path, AttributeProfiler::new());
+        let records = profiler.records();
+        assert!(records.contains(&Record::new(
+            "event",
+            "concept:name",
+            "type:string",
+            23_usize
+        )));
+        assert!(records.contains(
+            &Record::new("event_values", "concept:name", "count", 1_usize).with_item("e")
+        ));
+        assert!(records.contains(&Record::new(
+            "event",
+            "time:timestamp",
+            "date_max",
+            "2010-10-27T22:34:19.495+02:00"
+        )));
+
+        let sketch = observe(&path, SketchStats::new());
+        assert!(sketch
+            .records()
+            .contains(&Record::new("top_activities", "e", "error", 0_usize)));
+        assert!(sketch.to_json().starts_with("{\"stats\": \"SketchStats\""));
     }
 }
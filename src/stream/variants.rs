@@ -0,0 +1,346 @@
+//! Trace variants.
+//!
+//! A trace variant is the sequence of identities of a trace's events, as assigned by an event
+//! classifier. Typically, few variants cover the majority of cases in a log. Keeping only these is
+//! a common way to clean a log before process discovery.
+//!
+
+// standard library
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+// third party
+
+// local
+use crate::error::{Error, Result};
+use crate::stream::buffer::Buffer;
+use crate::stream::{Element, Meta, ResOpt, Stream};
+use crate::{Classifier, Identity, Scope, Trace};
+
+/// Sequence of event identities
+pub type Variant = Vec<Identity>;
+
+/// Trace variants along with the number of traces that share them
+///
+/// Variants are ordered by descending count. Variants of equal count retain the order of their
+/// first occurrence.
+///
+#[derive(Debug, Clone, Default)]
+pub struct Variants {
+    counts: Vec<(Variant, usize)>,
+}
+
+impl Variants {
+    /// Variants and their counts
+    pub fn counts(&self) -> &[(Variant, usize)] {
+        &self.counts
+    }
+
+    /// Number of distinct variants
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Number of traces
+    pub fn traces(&self) -> usize {
+        self.counts.iter().map(|(_, c)| c).sum()
+    }
+
+    /// Number of leading variants covered by a selection
+    fn selected(&self, selection: &Selection) -> usize {
+        match selection {
+            Selection::TopK(k) => (*k).min(self.len()),
+            Selection::Coverage(threshold) => {
+                let total = self.traces() as f64;
+                let mut covered: usize = 0;
+
+                for (i, (_, count)) in self.counts.iter().enumerate() {
+                    if covered as f64 >= threshold * total {
+                        return i;
+                    }
+                    covered += count;
+                }
+
+                self.len()
+            }
+        }
+    }
+}
+
+/// Which variants to keep
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    /// The `k` most frequent variants
+    TopK(usize),
+    /// The most frequent variants that together cover at least the given share of traces
+    Coverage(f64),
+}
+
+/// Keep only traces of frequent variants
+///
+/// In a first pass, the buffered stream is analyzed and trace variants are counted. In a second
+/// pass, the stream is forwarded while traces of variants that are not selected are dropped.
+///
+/// Event identities are computed by the given event classifier, looked up by name in the stream's
+/// meta data, or by `concept:name` if none is given. Classifiers that target traces are rejected.
+/// Events the classifier yields no identity for are represented by an empty identity, i.e. one
+/// without any values.
+///
+#[derive(Debug)]
+pub struct VariantFilter {
+    buffer: Buffer,
+    variants: Variants,
+    trace_variants: Vec<usize>,
+    ct_trace: usize,
+    selected: usize,
+}
+
+impl VariantFilter {
+    /// Analyze a buffered stream, e.g. from a `Log`, and select variants
+    pub fn new(
+        buffer: Buffer,
+        classifier_name: Option<&str>,
+        selection: Selection,
+    ) -> Result<Self> {
+        let mut buffer = buffer;
+        let mut analyzed = Buffer::default();
+        let mut meta = Meta::default();
+        let mut classifier: Option<Classifier> = None;
+
+        let mut indices: HashMap<Variant, usize> = HashMap::new();
+        let mut counts: Vec<(Variant, usize)> = Vec::new();
+        let mut trace_variants: Vec<usize> = Vec::new();
+
+        while let Some(element) = buffer.next()? {
+            meta.update(&element)?;
+
+            if let Element::Trace(trace) = &element {
+                if classifier.is_none() {
+                    classifier = Some(select_classifier(classifier_name, &meta)?);
+                }
+
+                let variant = variant(trace, classifier.as_ref().unwrap(), &meta);
+                let index = *indices.entry(variant.clone()).or_insert_with(|| {
+                    counts.push((variant, 0));
+                    counts.len() - 1
+                });
+
+                counts[index].1 += 1;
+                trace_variants.push(index);
+            }
+
+            analyzed.push(Ok(Some(element)));
+        }
+
+        // rank variants by count, stable with respect to first occurrence
+        let mut ranking: Vec<usize> = (0..counts.len()).collect();
+        ranking.sort_by(|a, b| counts[*b].1.cmp(&counts[*a].1));
+
+        let mut rank = vec![0; counts.len()];
+        for (r, i) in ranking.iter().enumerate() {
+            rank[*i] = r;
+        }
+
+        let variants = Variants {
+            counts: ranking.iter().map(|i| counts[*i].clone()).collect(),
+        };
+        let selected = variants.selected(&selection);
+
+        Ok(VariantFilter {
+            buffer: analyzed,
+            variants,
+            trace_variants: trace_variants.into_iter().map(|i| rank[i]).collect(),
+            ct_trace: 0,
+            selected,
+        })
+    }
+
+    /// Variants found in the stream
+    pub fn variants(&self) -> &Variants {
+        &self.variants
+    }
+
+    /// Variants that pass the filter
+    pub fn selected(&self) -> &[(Variant, usize)] {
+        &self.variants.counts[..self.selected]
+    }
+}
+
+impl Stream for VariantFilter {
+    fn next(&mut self) -> ResOpt {
+        while let Some(element) = self.buffer.next()? {
+            if let Element::Trace(_) = element {
+                let rank = self.trace_variants[self.ct_trace];
+                self.ct_trace += 1;
+
+                if rank >= self.selected {
+                    continue;
+                }
+            }
+
+            return Ok(Some(element));
+        }
+
+        Ok(None)
+    }
+}
+
+pub(crate) fn select_classifier(name: Option<&str>, meta: &Meta) -> Result<Classifier> {
+    match name {
+        Some(name) => match meta.classifier(name) {
+            Some(classifier) if *classifier.scope() == Scope::Event => Ok(classifier.clone()),
+            Some(_) => Err(Error::KeyError(format!(
+                "classifier {:?} with event scope",
+                name
+            ))),
+            None => Err(Error::KeyError(format!("classifier {:?}", name))),
+        },
+        None => Ok(Classifier::new(
+            "concept:name",
+            Some(Scope::Event),
+            meta.key("concept:name"),
+        )),
+    }
+}
+
+fn variant(trace: &Trace, classifier: &Classifier, meta: &Meta) -> Variant {
+    trace
+        .events()
+        .iter()
+        .map(|e| {
+            classifier
+                .event_identity(e, meta.globals())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::buffer::tests::load_example;
+    use crate::stream::StreamSink;
+    use crate::Log;
+
+    fn filter_example(classifier: Option<&str>, selection: Selection) -> (Log, VariantFilter) {
+        let buffer = load_example(&["xes", "book", "L1.xes"]);
+        let mut filter = VariantFilter::new(buffer, classifier, selection).unwrap();
+        let mut log = Log::default();
+
+        log.consume(&mut filter).unwrap();
+
+        (log, filter)
+    }
+
+    fn names(log: &Log) -> Vec<String> {
+        let meta = Meta::from(log);
+        log.traces()
+            .iter()
+            .map(|t| t.name(&meta).unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_variants() {
+        let (log, filter) = filter_example(None, Selection::TopK(usize::MAX));
+        let variants = filter.variants();
+
+        let expected: Vec<(Variant, usize)> = [("abcd", 3), ("acbd", 2), ("aed", 1)]
+            .iter()
+            .map(|(v, c)| (v.chars().map(|c| vec![c.to_string()]).collect(), *c))
+            .collect();
+
+        assert_eq!(variants.counts(), expected.as_slice());
+        assert_eq!(variants.len(), 3);
+        assert_eq!(variants.traces(), 6);
+        assert_eq!(log.traces().len(), 6);
+        assert_eq!(log.extensions().len(), 5);
+        assert_eq!(log.attributes().len(), 3);
+
+        let (_, filter) = filter_example(Some("MXMLLegacyClassifier"), Selection::TopK(1));
+        assert_eq!(
+            filter.selected()[0].0,
+            ["a", "b", "c", "d"]
+                .iter()
+                .map(|a| vec![a.to_string(), String::from("complete")])
+                .collect::<Variant>()
+        );
+
+        assert!(VariantFilter::new(
+            load_example(&["xes", "book", "L1.xes"]),
+            Some("fnord"),
+            Selection::TopK(1)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_classifier_scope() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <classifier name="Case" scope="trace" keys="concept:name"/>
+                <classifier name="Activity" scope="event" keys="concept:name"/>
+                <trace>
+                    <string key="concept:name" value="case 1"/>
+                    <event>
+                        <string key="concept:name" value="a"/>
+                    </event>
+                    <event/>
+                </trace>
+            </log>"#;
+        let filter = |classifier: &str| {
+            let log: Log = s.parse().unwrap();
+            VariantFilter::new(Buffer::from(log), Some(classifier), Selection::TopK(1))
+        };
+
+        match filter("Case") {
+            Err(Error::KeyError(message)) => assert!(message.contains("event scope")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // events without identity contribute an empty one
+        let filter = filter("Activity").unwrap();
+        assert_eq!(
+            filter.selected()[0].0,
+            vec![vec![String::from("a")], Identity::new()]
+        );
+    }
+
+    #[test]
+    fn test_top_k() {
+        let (log, _) = filter_example(None, Selection::TopK(0));
+        assert!(log.traces().is_empty());
+
+        let (log, _) = filter_example(None, Selection::TopK(1));
+        assert_eq!(names(&log), vec!["Case1.2", "Case1.1", "Case1.0"]);
+
+        let (log, filter) = filter_example(None, Selection::TopK(2));
+        assert_eq!(
+            names(&log),
+            vec!["Case2.0", "Case1.2", "Case1.1", "Case1.0", "Case2.1"]
+        );
+        assert_eq!(filter.selected().len(), 2);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let param = [
+            (0.0, 0),
+            (0.3, 3),
+            (0.5, 3),
+            (0.51, 5),
+            (0.8, 5),
+            (0.9, 6),
+            (1.0, 6),
+        ];
+
+        for (threshold, expected) in param.iter() {
+            let (log, _) = filter_example(None, Selection::Coverage(*threshold));
+            assert_eq!(log.traces().len(), *expected, "{}", threshold);
+        }
+    }
+}
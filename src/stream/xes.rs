@@ -19,6 +19,13 @@
 //!     file.xes
 //! ```
 //!
+//! Beyond the schema, `XesValidator` checks the semantic constraints of the standard, such as
+//! globals, classifiers and declared extensions, while streaming.
+//!
+//! Gzip compressed XES (`.xes.gz`) is read by `XesReader::detect`, which detects compression on its
+//! own, and written by `XesWriter::gzip`. Wrapping the input in a `CountingReader` tracks the
+//! number of bytes read, e.g. for a `stats::Counter`.
+//!
 //! # Example
 //! This example illustrates how to serialize XES XML from a string and deserialize it to stdout.
 //! ```
@@ -47,12 +54,18 @@
 //!
 
 // standard library
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::convert::{From, TryFrom};
+use std::fmt;
 use std::fmt::Debug;
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 // third party
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use quick_xml::events::{
     BytesDecl as QxBytesDecl, BytesEnd as QxBytesEnd, BytesStart as QxBytesStart,
     BytesText as QxBytesText, Event as QxEvent,
@@ -60,13 +73,16 @@ use quick_xml::events::{
 use quick_xml::{Reader as QxReader, Writer as QxWriter};
 
 // local
-use crate::error::{Error, Result};
+use crate::error::{Error, Position, Result};
+use crate::stream::xesext::{ExtensionScope, Registry, ValueType};
 use crate::stream::xml_util::{
-    parse_bool, validate_name, validate_ncname, validate_token, validate_uri,
+    parse_bool, validate_decimal, validate_name, validate_ncname, validate_token, validate_uri,
+    validate_uuid,
 };
-use crate::stream::{Element, ResOpt, Stream, StreamSink};
+use crate::stream::{Element, Meta, ResOpt, Stream, StreamSink, StreamState};
 use crate::{
-    Attribute, AttributeType, Classifier, DateTime, Event, Extension, Global, Log, Scope, Trace,
+    Attribute, AttributeType, Classifier, DateTime, Event, Extension, Global, Header, Log, Scope,
+    Trace,
 };
 
 #[derive(Debug)]
@@ -78,11 +94,14 @@ enum XesElement {
     Classifier(Classifier),
     Event(Event),
     Trace(Trace),
+    // only ever reported as unsupported while streaming
+    #[allow(dead_code)]
     Log(Log),
 }
 
 #[derive(Debug, Clone)]
 struct XesValue {
+    key: Option<String>,
     attributes: Vec<Attribute>,
 }
 
@@ -92,6 +111,9 @@ impl TryFrom<XesIntermediate> for Attribute {
     fn try_from(intermediate: XesIntermediate) -> Result<Self> {
         let key_str = intermediate.get_attr("key")?.clone();
         let val_str = intermediate.get_attr("value");
+        let mut values: Vec<Attribute> = Vec::new();
+        let mut keys: Vec<Option<String>> = Vec::new();
+        let mut attributes: Vec<Attribute> = Vec::new();
 
         let value = match intermediate.type_name.as_str() {
             "string" => Ok(AttributeType::String(val_str?.clone())),
@@ -100,26 +122,33 @@ impl TryFrom<XesIntermediate> for Attribute {
             )?)),
             "int" => Ok(AttributeType::Int(val_str?.parse::<i64>()?)),
             "float" => Ok(AttributeType::Float(val_str?.parse::<f64>()?)),
-            "boolean" => Ok(AttributeType::Boolean(parse_bool(&val_str?.as_str())?)),
+            "boolean" => Ok(AttributeType::Boolean(parse_bool(val_str?.as_str())?)),
             "id" => Ok(AttributeType::Id(val_str?.clone())),
-            "list" => {
-                let mut attributes: Vec<Attribute> = Vec::new();
+            "list" => Ok(AttributeType::List(Vec::new())),
+            attr_key => Err(Error::KeyError(format!("unknown attribute {}", attr_key))),
+        }?;
 
-                for values in intermediate.elements {
-                    match values {
-                        XesElement::Value(v) => attributes.extend(v.attributes),
-                        _ => ( /* ignore */ ),
-                    }
+        for element in intermediate.elements {
+            match element {
+                XesElement::Value(v) => {
+                    keys.push(v.key);
+                    values.extend(v.attributes);
                 }
-
-                Ok(AttributeType::List(attributes))
+                XesElement::Attribute(a) => attributes.push(a),
+                other => warn!("unexpected child element of attribute: {:?}, ignore", other),
             }
-            attr_key => Err(Error::KeyError(format!("unknown attribute {}", attr_key))),
+        }
+
+        let (value, values) = match value {
+            AttributeType::List(_) => (AttributeType::List(values), Some(keys)),
+            other => (other, None),
         };
 
         Ok(Attribute {
             key: key_str,
-            value: value?,
+            value,
+            attributes,
+            values,
         })
     }
 }
@@ -149,11 +178,18 @@ impl Attribute {
                 let tag_l = b"list";
                 let tag_v = b"values";
                 let mut event_l = QxBytesStart::owned(tag_l.to_vec(), tag_l.len());
-                let event_v = QxBytesStart::owned(tag_v.to_vec(), tag_v.len());
+                let mut event_v = QxBytesStart::owned(tag_v.to_vec(), tag_v.len());
 
+                // the schema requires a key for `<values>`, which carries no meaning
                 event_l.push_attribute(("key", validate_name(self.key.as_str())?));
+                event_v.push_attribute(("key", self.key.as_str()));
 
                 bytes += writer.write_event(QxEvent::Start(event_l))?;
+
+                for attribute in self.attributes.iter() {
+                    bytes += attribute.write_xes(writer)?;
+                }
+
                 bytes += writer.write_event(QxEvent::Start(event_v))?;
 
                 for attribute in attributes.iter() {
@@ -173,7 +209,19 @@ impl Attribute {
         event.push_attribute(("key", validate_name(self.key.as_str())?));
         event.push_attribute(("value", value));
 
-        Ok(writer.write_event(QxEvent::Empty(event))?)
+        if self.attributes.is_empty() {
+            return Ok(writer.write_event(QxEvent::Empty(event))?);
+        }
+
+        let mut bytes = writer.write_event(QxEvent::Start(event))?;
+
+        for attribute in self.attributes.iter() {
+            bytes += attribute.write_xes(writer)?;
+        }
+
+        bytes += writer.write_event(QxEvent::End(QxBytesEnd::borrowed(tag)))?;
+
+        Ok(bytes)
     }
 }
 
@@ -190,7 +238,10 @@ impl TryFrom<XesIntermediate> for XesValue {
             }
         }
 
-        Ok(XesValue { attributes })
+        Ok(XesValue {
+            key: intermediate.attributes.get("key").cloned(),
+            attributes,
+        })
     }
 }
 
@@ -223,7 +274,7 @@ impl TryFrom<XesIntermediate> for Global {
     type Error = Error;
 
     fn try_from(intermediate: XesIntermediate) -> Result<Self> {
-        let scope = Scope::try_from(intermediate.attributes.get("scope").cloned())?;
+        let scope = intermediate.scope()?;
         let mut attributes: Vec<Attribute> = Vec::new();
 
         for element in intermediate.elements {
@@ -244,8 +295,9 @@ impl Global {
         let mut event = QxBytesStart::owned(tag.to_vec(), tag.len());
 
         match self.scope {
-            Scope::Event => event.push_attribute(("scope", "event")),
-            Scope::Trace => event.push_attribute(("scope", "trace")),
+            Some(Scope::Event) => event.push_attribute(("scope", "event")),
+            Some(Scope::Trace) => event.push_attribute(("scope", "trace")),
+            None => (),
         }
 
         bytes += writer.write_event(QxEvent::Start(event))?;
@@ -264,11 +316,11 @@ impl TryFrom<XesIntermediate> for Classifier {
     type Error = Error;
 
     fn try_from(intermediate: XesIntermediate) -> Result<Self> {
-        Ok(Classifier {
-            name: intermediate.get_attr("name")?.clone(),
-            scope: Scope::try_from(intermediate.attributes.get("scope").cloned())?,
-            keys: intermediate.get_attr("keys")?.clone(),
-        })
+        Ok(Classifier::new(
+            intermediate.get_attr("name")?.clone(),
+            intermediate.scope()?,
+            intermediate.get_attr("keys")?.clone(),
+        ))
     }
 }
 
@@ -279,8 +331,9 @@ impl Classifier {
 
         event.push_attribute(("name", validate_ncname(self.name.as_str())?));
         match self.scope {
-            Scope::Event => event.push_attribute(("scope", "event")),
-            Scope::Trace => event.push_attribute(("scope", "trace")),
+            Some(Scope::Event) => event.push_attribute(("scope", "event")),
+            Some(Scope::Trace) => event.push_attribute(("scope", "trace")),
+            None => (),
         }
         event.push_attribute(("keys", validate_token(self.keys.as_str())?));
 
@@ -367,10 +420,25 @@ impl Trace {
     }
 }
 
+impl From<&XesIntermediate> for Header {
+    fn from(intermediate: &XesIntermediate) -> Self {
+        let features = match intermediate.attributes.get("xes.features") {
+            Some(features) => features.split_whitespace().map(String::from).collect(),
+            None => Vec::new(),
+        };
+
+        Header {
+            version: intermediate.attributes.get("xes.version").cloned(),
+            features,
+        }
+    }
+}
+
 impl TryFrom<XesIntermediate> for Log {
     type Error = Error;
 
     fn try_from(intermediate: XesIntermediate) -> Result<Self> {
+        let header = Header::from(&intermediate);
         let mut extensions: Vec<Extension> = Vec::new();
         let mut globals: Vec<Global> = Vec::new();
         let mut classifiers: Vec<Classifier> = Vec::new();
@@ -391,6 +459,7 @@ impl TryFrom<XesIntermediate> for Log {
         }
 
         Ok(Log {
+            header: Some(header),
             extensions,
             globals,
             classifiers,
@@ -429,10 +498,11 @@ struct XesIntermediate {
     type_name: String,
     attributes: HashMap<String, String>,
     elements: Vec<XesElement>,
+    position: Position,
 }
 
 impl XesIntermediate {
-    fn from_event(event: QxBytesStart) -> Result<Self> {
+    fn from_event(event: QxBytesStart, position: Position) -> Result<Self> {
         let mut attr: HashMap<String, String> = HashMap::new();
 
         for attribute in event.attributes() {
@@ -447,6 +517,7 @@ impl XesIntermediate {
             type_name: String::from_utf8(event.name().to_vec())?,
             attributes: attr,
             elements: Vec::new(),
+            position,
         })
     }
 
@@ -460,24 +531,185 @@ impl XesIntermediate {
         }
     }
 
+    fn scope(&self) -> Result<Option<Scope>> {
+        self.attributes
+            .get("scope")
+            .map(|s| Scope::try_from(s.as_str()))
+            .transpose()
+    }
+
     fn add_element(&mut self, element: XesElement) {
         self.elements.push(element)
     }
 }
 
+/// Number of lines and start of the current line of consumed input
+#[derive(Debug, Default)]
+struct Lines {
+    count: AtomicUsize,
+    start: AtomicUsize,
+}
+
+/// Keep track of lines while input is consumed
+///
+/// Each byte is scanned for newlines once, when it becomes available by `fill_buf` or `read`. The
+/// offsets of newlines are queued until the bytes are consumed.
+///
+struct LineReader<R: io::BufRead> {
+    reader: R,
+    offset: usize,
+    scanned: usize,
+    newlines: VecDeque<usize>,
+    lines: Arc<Lines>,
+}
+
+impl<R: io::BufRead> LineReader<R> {
+    fn new(reader: R, lines: Arc<Lines>) -> Self {
+        LineReader {
+            reader,
+            offset: 0,
+            scanned: 0,
+            newlines: VecDeque::new(),
+            lines,
+        }
+    }
+
+    /// Mark input up to the given offset as consumed
+    fn advance(&mut self, offset: usize) {
+        let mut count = 0;
+        let mut last = None;
+
+        while let Some(newline) = self.newlines.front() {
+            if *newline >= offset {
+                break;
+            }
+
+            count += 1;
+            last = self.newlines.pop_front();
+        }
+
+        if let Some(last) = last {
+            self.lines.count.fetch_add(count, Ordering::Relaxed);
+            self.lines.start.store(last + 1, Ordering::Relaxed);
+        }
+
+        self.offset = offset;
+        self.scanned = self.scanned.max(offset);
+    }
+}
+
+/// Queue offsets of newlines in bytes that start at `offset`, skipping those scanned already
+fn scan(newlines: &mut VecDeque<usize>, scanned: &mut usize, offset: usize, bytes: &[u8]) {
+    let end = offset + bytes.len();
+
+    if end > *scanned {
+        let mut start = *scanned - offset;
+
+        while let Some(i) = bytes[start..].iter().position(|byte| *byte == b'\n') {
+            newlines.push_back(offset + start + i);
+            start += i + 1;
+        }
+
+        *scanned = end;
+    }
+}
+
+impl<R: io::BufRead> io::Read for LineReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        scan(
+            &mut self.newlines,
+            &mut self.scanned,
+            self.offset,
+            &buf[..read],
+        );
+        self.advance(self.offset + read);
+        Ok(read)
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for LineReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buf = self.reader.fill_buf()?;
+        scan(&mut self.newlines, &mut self.scanned, self.offset, buf);
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.advance(self.offset + amt);
+        self.reader.consume(amt)
+    }
+}
+
+/// Indices of traces and events read so far
+#[derive(Debug, Default)]
+struct Indices {
+    ct_trace: usize,
+    ct_event: usize,
+    ct_sa_event: usize,
+}
+
+impl Indices {
+    /// Assign indices to an element that starts at the given depth
+    fn assign(&mut self, name: &[u8], depth: usize, position: &mut Position) {
+        if name == b"trace" && depth == 1 {
+            position.trace = Some(self.ct_trace);
+            self.ct_trace += 1;
+            self.ct_event = 0;
+        } else if name == b"event" && depth == 1 {
+            position.event = Some(self.ct_sa_event);
+            self.ct_sa_event += 1;
+        } else if name == b"event" && position.trace.is_some() && position.event.is_none() {
+            position.event = Some(self.ct_event);
+            self.ct_event += 1;
+        }
+    }
+}
+
 /// XML deserialization of XES
+///
+/// Errors are located in the input by `Error::PositionError`, `Error::inner` is the underlying
+/// error. Malformed XML is reported as `Error::XesError`.
+///
 pub struct XesReader<R: io::BufRead> {
-    reader: QxReader<R>,
+    reader: QxReader<LineReader<R>>,
+    lines: Arc<Lines>,
     buffer: Vec<u8>,
     stack: Vec<XesIntermediate>,
+    indices: Indices,
 }
 
 impl<R: io::BufRead> XesReader<R> {
     pub fn new(reader: R) -> Self {
+        let lines = Arc::new(Lines::default());
+        let reader = LineReader::new(reader, lines.clone());
+
         XesReader {
             reader: QxReader::from_reader(reader),
+            lines,
             buffer: Vec::new(),
             stack: Vec::new(),
+            indices: Indices::default(),
+        }
+    }
+
+    /// Position of the reader within the enclosing trace and event
+    fn position(&self) -> Position {
+        // input is consumed up to the current position only, hence lines are up to date
+        let offset = self.reader.buffer_position();
+        let line_start = self.lines.start.load(Ordering::Relaxed);
+
+        let (trace, event) = match self.stack.last() {
+            Some(parent) => (parent.position.trace, parent.position.event),
+            None => (None, None),
+        };
+
+        Position {
+            line: self.lines.count.load(Ordering::Relaxed) + 1,
+            column: offset.saturating_sub(line_start) + 1,
+            offset,
+            trace,
+            event,
         }
     }
 }
@@ -488,6 +720,142 @@ impl<R: io::BufRead> From<R> for XesReader<R> {
     }
 }
 
+impl<R: io::BufRead> XesReader<Decompressed<R>> {
+    /// Create a reader that transparently decompresses gzip input, detected by its magic bytes
+    pub fn detect(reader: R) -> Result<Self> {
+        Ok(XesReader::new(Decompressed::detect(reader)?))
+    }
+}
+
+/// Count bytes read from a reader
+///
+/// The count is shared, e.g. with a `Counter` further down the pipeline, see
+/// `CountingReader::bytes`.
+///
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    reader: R,
+    bytes: Arc<AtomicUsize>,
+}
+
+impl<R> CountingReader<R> {
+    /// Wrap a reader
+    pub fn new(reader: R) -> Self {
+        CountingReader {
+            reader,
+            bytes: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Shared number of bytes read so far
+    pub fn bytes(&self) -> Arc<AtomicUsize> {
+        self.bytes.clone()
+    }
+}
+
+impl<R: io::Read> io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.bytes.fetch_add(read, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for CountingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.bytes.fetch_add(amt, Ordering::Relaxed);
+        self.reader.consume(amt)
+    }
+}
+
+/// Input whose first bytes were read ahead, they are replayed before the remaining input
+pub type Peeked<R> = io::Chain<io::Cursor<Vec<u8>>, R>;
+
+/// Input that is decompressed if necessary
+///
+/// Gzip compression is detected by the magic bytes at the beginning of the input, see
+/// `Decompressed::detect`. Either way, the reader yields the plain XES XML.
+///
+#[derive(Debug)]
+pub enum Decompressed<R: io::BufRead> {
+    /// Uncompressed input, passed through
+    Plain(Peeked<R>),
+    /// Gzip compressed input, possibly consisting of several members
+    Gzip(io::BufReader<MultiGzDecoder<Peeked<R>>>),
+}
+
+impl<R: io::BufRead> Decompressed<R> {
+    /// Detect gzip compression by its magic bytes
+    ///
+    /// Reads until two bytes are available or the input ends, as the underlying reader may return
+    /// fewer bytes at a time. Bytes read ahead are replayed, i.e. no input is lost.
+    ///
+    pub fn detect(mut reader: R) -> Result<Self> {
+        let mut magic = Vec::with_capacity(2);
+
+        while magic.len() < 2 {
+            let available = match reader.fill_buf() {
+                Ok(buffer) => buffer,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+
+            if available.is_empty() {
+                break;
+            }
+
+            let amount = available.len().min(2 - magic.len());
+            magic.extend_from_slice(&available[..amount]);
+            reader.consume(amount);
+        }
+
+        let gzip = magic == [0x1f, 0x8b];
+        let reader = io::Read::chain(io::Cursor::new(magic), reader);
+
+        if gzip {
+            Ok(Decompressed::Gzip(io::BufReader::new(MultiGzDecoder::new(
+                reader,
+            ))))
+        } else {
+            Ok(Decompressed::Plain(reader))
+        }
+    }
+
+    /// Whether the input is gzip compressed
+    pub fn is_gzip(&self) -> bool {
+        matches!(self, Decompressed::Gzip(_))
+    }
+}
+
+impl<R: io::BufRead> io::Read for Decompressed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Decompressed::Plain(reader) => reader.read(buf),
+            Decompressed::Gzip(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<R: io::BufRead> io::BufRead for Decompressed<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Decompressed::Plain(reader) => reader.fill_buf(),
+            Decompressed::Gzip(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Decompressed::Plain(reader) => reader.consume(amt),
+            Decompressed::Gzip(reader) => reader.consume(amt),
+        }
+    }
+}
+
 // impl<R: io::Read> From<R> for XesReader<io::BufReader<R>> {
 //     fn from(reader: R) -> Self {
 //         XesReader::new(io::BufReader::new(reader))
@@ -497,16 +865,35 @@ impl<R: io::BufRead> From<R> for XesReader<R> {
 impl<T: io::BufRead> Stream for XesReader<T> {
     fn next(&mut self) -> ResOpt {
         let mut top_level_element: Option<XesElement> = None;
+        let mut top_level_position: Option<Position> = None;
 
         loop {
+            let mut position = self.position();
+
             match self.reader.read_event(&mut self.buffer) {
-                Ok(QxEvent::Start(event)) => self.stack.push(XesIntermediate::from_event(event)?),
+                Ok(QxEvent::Start(event)) => {
+                    self.indices
+                        .assign(event.name(), self.stack.len(), &mut position);
+                    let intermediate = XesIntermediate::from_event(event, position.clone())
+                        .map_err(|e| e.at(position))?;
+
+                    if self.stack.is_empty() && intermediate.type_name == "log" {
+                        let header = Header::from(&intermediate);
+                        self.stack.push(intermediate);
+                        return Ok(Some(Element::Header(header)));
+                    }
+
+                    self.stack.push(intermediate);
+                }
                 Ok(QxEvent::End(_event)) => {
                     let intermediate = self.stack.pop().unwrap();
-                    let element = XesElement::try_from(intermediate)?;
+                    let position = intermediate.position.clone();
+                    let element =
+                        XesElement::try_from(intermediate).map_err(|e| e.at(position.clone()))?;
 
                     if self.stack.len() == 1 {
                         top_level_element = Some(element);
+                        top_level_position = Some(position);
                         break;
                     } else {
                         match self.stack.last_mut() {
@@ -516,13 +903,22 @@ impl<T: io::BufRead> Stream for XesReader<T> {
                     }
                 }
                 Ok(QxEvent::Empty(event)) => {
-                    let element = {
-                        let intermediate = XesIntermediate::from_event(event)?;
-                        XesElement::try_from(intermediate)?
-                    };
+                    self.indices
+                        .assign(event.name(), self.stack.len(), &mut position);
+                    let intermediate = XesIntermediate::from_event(event, position.clone())
+                        .map_err(|e| e.at(position))?;
+
+                    if self.stack.is_empty() && intermediate.type_name == "log" {
+                        return Ok(Some(Element::Header(Header::from(&intermediate))));
+                    }
+
+                    let position = intermediate.position.clone();
+                    let element =
+                        XesElement::try_from(intermediate).map_err(|e| e.at(position.clone()))?;
 
                     if self.stack.len() == 1 {
                         top_level_element = Some(element);
+                        top_level_position = Some(position);
                         break;
                     } else {
                         match self.stack.last_mut() {
@@ -532,14 +928,10 @@ impl<T: io::BufRead> Stream for XesReader<T> {
                     }
                 }
                 Err(e) => {
-                    return Err(Error::XesError(format!(
-                        "Error at position {}: {:?}",
-                        self.reader.buffer_position(),
-                        e
-                    )));
+                    return Err(Error::XesError(format!("{:?}", e)).at(self.position()));
                 }
                 Ok(QxEvent::Eof) => {
-                    if self.buffer.len() == 0 {
+                    if self.buffer.is_empty() {
                         return Err(Error::XesError(String::from("No root element found")));
                     }
                     break;
@@ -557,19 +949,32 @@ impl<T: io::BufRead> Stream for XesReader<T> {
             Some(XesElement::Attribute(a)) => Ok(Some(Element::Attribute(a))),
             Some(XesElement::Trace(t)) => Ok(Some(Element::Trace(t))),
             Some(XesElement::Event(e)) => Ok(Some(Element::Event(e))),
-            Some(e) => Err(Error::XesError(format!(
-                "XES element is not supported for streaming: {:?}",
-                e
-            ))),
+            Some(e) => {
+                let error = Error::XesError(format!(
+                    "XES element is not supported for streaming: {:?}",
+                    e
+                ));
+
+                match top_level_position {
+                    Some(position) => Err(error.at(position)),
+                    None => Err(error),
+                }
+            }
             None => Ok(None),
         }
     }
 }
 
 /// XML serialization of XES
+///
+/// The log's start tag is deferred until the first element is written. This way, a leading
+/// `Element::Header` determines the declared features. Regardless of the header's version, the
+/// output declares IEEE Std 1849-2016.
+///
 pub struct XesWriter<W: io::Write> {
     writer: QxWriter<W>,
     bytes_written: usize,
+    log_open: bool,
 }
 
 impl<W: io::Write> XesWriter<W> {
@@ -583,8 +988,26 @@ impl<W: io::Write> XesWriter<W> {
         XesWriter {
             writer,
             bytes_written: 0,
+            log_open: false,
         }
     }
+
+    fn write_log_start(&mut self, header: Option<&Header>) -> Result<()> {
+        let tag = b"log";
+        let mut event = QxBytesStart::owned(tag.to_vec(), tag.len());
+        let features = match header {
+            Some(header) => header.features.join(" "),
+            None => String::new(),
+        };
+
+        event.push_attribute(("xes.version", "1849.2016"));
+        event.push_attribute(("xes.features", validate_token(features.as_str())?));
+
+        self.bytes_written += self.writer.write_event(QxEvent::Start(event))?;
+        self.log_open = true;
+
+        Ok(())
+    }
 }
 
 impl<W: io::Write> StreamSink for XesWriter<W> {
@@ -609,22 +1032,26 @@ impl<W: io::Write> StreamSink for XesWriter<W> {
             self.writer
                 .write_event(QxEvent::Comment(QxBytesText::from_plain_str(s)))
         })
-        .fold(Ok(0), |s: Result<usize>, v| Ok(s? + v?))?;
-
-        // write contents
-        let tag = b"log";
-        let mut event = QxBytesStart::owned(tag.to_vec(), tag.len());
-
-        event.push_attribute(("xes.version", "1849.2016"));
-        event.push_attribute(("xes.features", ""));
-
-        self.bytes_written += self.writer.write_event(QxEvent::Start(event))?;
+        .try_fold(0, |s, v| v.map(|v| s + v))?;
 
         Ok(())
     }
 
     fn on_element(&mut self, element: Element) -> Result<()> {
+        if !self.log_open {
+            if let Element::Header(header) = &element {
+                return self.write_log_start(Some(header));
+            }
+
+            self.write_log_start(None)?;
+        }
+
         self.bytes_written += match element {
+            Element::Header(_) => {
+                return Err(Error::XesError(String::from(
+                    "header must be the first element of a stream",
+                )))
+            }
             Element::Extension(e) => e.write_xes(&mut self.writer)?,
             Element::Global(g) => g.write_xes(&mut self.writer)?,
             Element::Classifier(c) => c.write_xes(&mut self.writer)?,
@@ -637,6 +1064,10 @@ impl<W: io::Write> StreamSink for XesWriter<W> {
     }
 
     fn on_close(&mut self) -> Result<()> {
+        if !self.log_open {
+            self.write_log_start(None)?;
+        }
+
         let event = QxEvent::End(QxBytesEnd::borrowed(b"log"));
 
         self.bytes_written += self.writer.write_event(event)?;
@@ -658,10 +1089,127 @@ impl<W: io::Write> XesWriter<W> {
     }
 }
 
+impl<W: io::Write> XesWriter<GzEncoder<W>> {
+    /// Create a writer that gzip compresses its output
+    ///
+    /// The gzip trailer is written by `XesWriter::finish`. If omitted, it is written when the
+    /// writer is dropped, ignoring any errors.
+    ///
+    pub fn gzip(writer: W, indent_char: Option<u8>, indent_size: Option<usize>) -> Self {
+        XesWriter::new(
+            GzEncoder::new(writer, Compression::default()),
+            indent_char,
+            indent_size,
+        )
+    }
+
+    /// Complete the compressed output and release the underlying writer
+    pub fn finish(self) -> Result<W> {
+        Ok(self.into_inner().finish()?)
+    }
+}
+
+/// Rules checked by the `XesValidator`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// Elements occur in an order that differs from the one obligated by the standard
+    Order,
+    /// A string doesn't match its data type, e.g. `xs:NCName` or UUID
+    DataType,
+    /// An attribute key occurs more than once within the same component
+    DuplicateKey,
+    /// The prefix of an attribute key refers to an extension that is not declared
+    UndeclaredExtension,
+    /// An attribute is nested while the `nested-attributes` feature is not declared
+    UndeclaredNesting,
+    /// The `nested-attributes` feature is declared but no attribute is nested
+    UnusedNesting,
+    /// A trace or event lacks a global attribute of its scope
+    MissingGlobal,
+    /// The type of an attribute differs from the type of the corresponding global attribute
+    GlobalType,
+    /// The type of an attribute differs from the type declared by its extension
+    ExtensionType,
+    /// A classifier doesn't define any key
+    EmptyClassifier,
+    /// A classifier key is no global attribute of the classifier's scope
+    ClassifierKey,
+    /// A list doesn't contain exactly one `<values>` element with a key, as required by the schema
+    ListValues,
+}
+
+/// Violation of a rule found by the `XesValidator`
+///
+/// Besides the broken rule, a violation refers to the kind of the offending element. Traces are
+/// indexed in order of their occurrence, events either within their trace or, if not part of a
+/// trace, within the log.
+///
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub element: StreamState,
+    pub trace: Option<usize>,
+    pub event: Option<usize>,
+    pub key: Option<String>,
+    pub rule: Rule,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.element)?;
+
+        if let Some(trace) = self.trace {
+            write!(f, ", trace {}", trace)?;
+        }
+
+        if let Some(event) = self.event {
+            write!(f, ", event {}", event)?;
+        }
+
+        if let Some(key) = &self.key {
+            write!(f, ", key {:?}", key)?;
+        }
+
+        write!(f, ": {} ({:?})", self.message, self.rule)
+    }
+}
+
+// Location of the element that is currently validated
+#[derive(Debug, Clone)]
+struct Location {
+    element: StreamState,
+    trace: Option<usize>,
+    event: Option<usize>,
+}
+
+impl Location {
+    fn violation(&self, key: Option<&str>, rule: Rule, message: String) -> Violation {
+        Violation {
+            element: self.element.clone(),
+            trace: self.trace,
+            event: self.event,
+            key: key.map(String::from),
+            rule,
+            message,
+        }
+    }
+
+    fn data_type(&self, key: Option<&str>, result: Result<&str>) -> Option<Violation> {
+        match result {
+            Err(Error::ValidationError(message)) => {
+                Some(self.violation(key, Rule::DataType, message))
+            }
+            Err(error) => Some(self.violation(key, Rule::DataType, error.to_string())),
+            Ok(_) => None,
+        }
+    }
+}
+
 /// Validates an extensible event stream
 ///
 /// **Element level validation**
 /// * string types
+///     * `Header.version` (`xs:decimal`)
 ///     * `Attribute.key` (`xs:Name`)
 ///     * `Extension.name` (`xs:NCName`)
 ///     * `Extension.prefix` (`xs:NCName`)
@@ -670,12 +1218,437 @@ impl<W: io::Write> XesWriter<W> {
 ///     * `Classifier.name` (`xs:NCName`)
 ///     * `Classifier.scope` (`xs:NCName`)
 ///     * `Classifier.keys` (`xs:token`)
+/// * values of `id` attributes (UUID)
+/// * lists contain exactly one `<values>` element with a key, as read from XES
 ///
 /// **Semantic validation**
+/// * order of stream elements
+/// * attribute keys are unique within their component
+/// * prefixed attribute keys refer to a declared extension
+/// * attributes defined by an extension share the type declared by the extension for their scope,
+///   see `xesext::Registry`, definitions of declared extensions with a `file` URI are loaded if
+///   enabled by `with_local_extensions`
 /// * nested attributes
+///     * attributes that contain attributes require the `nested-attributes` feature, lists don't
+///     * the `nested-attributes` feature requires nested attributes to occur
 /// * globals
+///     * traces and events provide all global attributes of their scope
+///     * attributes share the type of the corresponding global attribute
+/// * classifiers
+///     * at least one key is given
+///     * keys refer to global attributes of the classifier's scope
+///
+/// Elements are forwarded as they are. By default, the first violation encountered is propagated
+/// as `Error::ValidationError`. In report mode, all violations are collected instead and the
+/// stream is forwarded completely. Once done, the collected violations can be released.
 ///
-pub struct XesValidator {/* TODO to be implemented */}
+pub struct XesValidator<T: Stream> {
+    stream: T,
+    meta: Meta,
+    registry: Registry,
+    local_extensions: bool,
+    report: bool,
+    violations: Vec<Violation>,
+    nested: bool,
+    ct_trace: usize,
+    ct_event: usize,
+}
+
+impl<T: Stream> XesValidator<T> {
+    /// Create a new validator from stream that fails on the first violation
+    pub fn new(stream: T) -> Self {
+        XesValidator {
+            stream,
+            meta: Meta::default(),
+            registry: Registry::default(),
+            local_extensions: false,
+            report: false,
+            violations: Vec::new(),
+            nested: false,
+            ct_trace: 0,
+            ct_event: 0,
+        }
+    }
+
+    /// Create a new validator from stream that collects all violations
+    pub fn reporting(stream: T) -> Self {
+        XesValidator {
+            report: true,
+            ..XesValidator::new(stream)
+        }
+    }
+
+    /// Use the given registry of extension definitions, e.g. one with user-defined extensions
+    pub fn with_registry(mut self, registry: Registry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Load definitions of declared extensions with a `file` URI, see `Registry::load_declared`
+    ///
+    /// By default, the validator doesn't touch the file system. As the URIs stem from the
+    /// validated stream, only enable this for trusted input.
+    ///
+    pub fn with_local_extensions(mut self) -> Self {
+        self.local_extensions = true;
+        self
+    }
+
+    /// Violations collected so far
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Release collected violations
+    pub fn release(&mut self) -> Vec<Violation> {
+        std::mem::take(&mut self.violations)
+    }
+
+    fn declares_nested(&self) -> bool {
+        match &self.meta.header {
+            Some(header) => header.features.iter().any(|f| f == "nested-attributes"),
+            None => false,
+        }
+    }
+
+    fn handle(&mut self, violations: Vec<Violation>) -> Result<()> {
+        for violation in violations {
+            if !self.report {
+                return Err(Error::ValidationError(violation.to_string()));
+            }
+
+            self.violations.push(violation);
+        }
+
+        Ok(())
+    }
+
+    fn validate_element(&mut self, element: &Element) -> Result<()> {
+        let mut location = Location {
+            element: StreamState::from(element),
+            trace: None,
+            event: None,
+        };
+        let mut violations: Vec<Violation> = Vec::new();
+
+        if let Err(error) = self.meta.update(element) {
+            violations.push(location.violation(None, Rule::Order, error.to_string()));
+        }
+
+        match element {
+            Element::Header(header) => {
+                if let Some(version) = &header.version {
+                    violations.extend(location.data_type(None, validate_decimal(version)));
+                }
+
+                let features = header.features.join(" ");
+                violations.extend(location.data_type(None, validate_token(features.as_str())));
+            }
+            Element::Extension(extension) => {
+                for result in [
+                    validate_ncname(extension.name.as_str()),
+                    validate_ncname(extension.prefix.as_str()),
+                    validate_uri(extension.uri.as_str()),
+                ] {
+                    violations.extend(location.data_type(None, result));
+                }
+
+                if self.local_extensions {
+                    if let Err(error) = self.registry.load_declared(extension) {
+                        warn!("failed to load extension {:?}: {:?}", extension.uri, error);
+                    }
+                }
+            }
+            Element::Global(global) => {
+                let scope = ExtensionScope::from(global.scope());
+                self.validate_attributes(&global.attributes, &scope, &location, &mut violations);
+            }
+            Element::Classifier(classifier) => {
+                self.validate_classifier(classifier, &location, &mut violations);
+            }
+            Element::Attribute(attribute) => {
+                let count = self
+                    .meta
+                    .attributes
+                    .iter()
+                    .filter(|a| a.key == attribute.key)
+                    .count();
+
+                if count > 1 {
+                    violations.push(location.violation(
+                        Some(&attribute.key),
+                        Rule::DuplicateKey,
+                        String::from("log attribute is not unique"),
+                    ));
+                }
+
+                self.validate_attribute(
+                    attribute,
+                    &ExtensionScope::Log,
+                    &location,
+                    &mut violations,
+                );
+            }
+            Element::Trace(trace) => {
+                location.trace = Some(self.ct_trace);
+                self.ct_trace += 1;
+
+                let scope = ExtensionScope::Trace;
+                self.validate_attributes(&trace.attributes, &scope, &location, &mut violations);
+                self.validate_globals(&trace.attributes, &location, &mut violations);
+
+                location.element = StreamState::Event;
+
+                for (i, event) in trace.events.iter().enumerate() {
+                    location.event = Some(i);
+                    let scope = ExtensionScope::Event;
+                    self.validate_attributes(&event.attributes, &scope, &location, &mut violations);
+                    self.validate_globals(&event.attributes, &location, &mut violations);
+                }
+            }
+            Element::Event(event) => {
+                location.event = Some(self.ct_event);
+                self.ct_event += 1;
+
+                let scope = ExtensionScope::Event;
+                self.validate_attributes(&event.attributes, &scope, &location, &mut violations);
+                self.validate_globals(&event.attributes, &location, &mut violations);
+            }
+        }
+
+        self.nested |= match element {
+            Element::Global(global) => global.attributes.iter().any(is_nested),
+            Element::Attribute(attribute) => is_nested(attribute),
+            Element::Trace(trace) => {
+                trace.attributes.iter().any(is_nested)
+                    || trace
+                        .events
+                        .iter()
+                        .any(|e| e.attributes.iter().any(is_nested))
+            }
+            Element::Event(event) => event.attributes.iter().any(is_nested),
+            _ => false,
+        };
+
+        self.handle(violations)
+    }
+
+    fn validate_classifier(
+        &self,
+        classifier: &Classifier,
+        location: &Location,
+        violations: &mut Vec<Violation>,
+    ) {
+        violations.extend(location.data_type(None, validate_ncname(classifier.name.as_str())));
+        violations.extend(location.data_type(None, validate_token(classifier.keys.as_str())));
+
+        let keys = classifier.keys();
+
+        if keys.is_empty() {
+            violations.push(location.violation(
+                None,
+                Rule::EmptyClassifier,
+                format!("classifier {:?} has no keys", classifier.name),
+            ));
+        }
+
+        let scope = classifier.scope().clone();
+
+        for key in keys.iter() {
+            if !self.globals(&scope).any(|g| &g.key == key) {
+                violations.push(location.violation(
+                    Some(key),
+                    Rule::ClassifierKey,
+                    format!(
+                        "key of classifier {:?} is no global attribute of scope {:?}",
+                        classifier.name, scope
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn validate_attributes(
+        &self,
+        attributes: &[Attribute],
+        scope: &ExtensionScope,
+        location: &Location,
+        violations: &mut Vec<Violation>,
+    ) {
+        for (i, attribute) in attributes.iter().enumerate() {
+            if attributes[..i].iter().any(|a| a.key == attribute.key) {
+                violations.push(location.violation(
+                    Some(&attribute.key),
+                    Rule::DuplicateKey,
+                    String::from("attribute is not unique"),
+                ));
+            }
+
+            self.validate_attribute(attribute, scope, location, violations);
+        }
+    }
+
+    fn validate_attribute(
+        &self,
+        attribute: &Attribute,
+        scope: &ExtensionScope,
+        location: &Location,
+        violations: &mut Vec<Violation>,
+    ) {
+        let key = Some(attribute.key.as_str());
+
+        violations.extend(location.data_type(key, validate_name(attribute.key.as_str())));
+
+        if let Some(i) = attribute.key.find(':') {
+            let prefix = &attribute.key[..i];
+
+            if !self.meta.extensions.iter().any(|e| e.prefix == prefix) {
+                violations.push(location.violation(
+                    key,
+                    Rule::UndeclaredExtension,
+                    format!("extension {:?} is not declared", prefix),
+                ));
+            }
+        }
+
+        if let Some(declared) = self
+            .registry
+            .resolve(scope, &attribute.key, &self.meta.extensions)
+        {
+            if ValueType::from(&attribute.value) != declared.value_type {
+                violations.push(location.violation(
+                    key,
+                    Rule::ExtensionType,
+                    format!(
+                        "attribute differs from type {:?} declared by extension",
+                        declared.value_type
+                    ),
+                ));
+            }
+        }
+
+        match &attribute.value {
+            AttributeType::Id(value) => {
+                violations.extend(location.data_type(key, validate_uuid(value.as_str())));
+            }
+            AttributeType::List(values) => {
+                match attribute.values.as_deref() {
+                    Some([Some(_)]) | None => (),
+                    Some([None]) => violations.push(location.violation(
+                        key,
+                        Rule::ListValues,
+                        String::from("`<values>` of list lacks a key"),
+                    )),
+                    Some(keys) => violations.push(location.violation(
+                        key,
+                        Rule::ListValues,
+                        format!(
+                            "list contains {} `<values>` elements instead of one",
+                            keys.len()
+                        ),
+                    )),
+                }
+
+                for value in values.iter() {
+                    self.validate_attribute(value, &ExtensionScope::Meta, location, violations);
+                }
+            }
+            _ => (),
+        };
+
+        if !attribute.attributes.is_empty() && !self.declares_nested() {
+            violations.push(location.violation(
+                key,
+                Rule::UndeclaredNesting,
+                String::from(
+                    "attribute is nested but feature \"nested-attributes\" is not declared",
+                ),
+            ));
+        }
+
+        let scope = &ExtensionScope::Meta;
+        self.validate_attributes(&attribute.attributes, scope, location, violations);
+    }
+
+    fn validate_globals(
+        &self,
+        attributes: &[Attribute],
+        location: &Location,
+        violations: &mut Vec<Violation>,
+    ) {
+        let scope = match location.element {
+            StreamState::Trace => Scope::Trace,
+            _ => Scope::Event,
+        };
+
+        for global in self.globals(&scope) {
+            let key = Some(global.key.as_str());
+
+            match attributes.iter().find(|a| a.key == global.key) {
+                Some(attribute) => {
+                    if ValueType::from(&attribute.value) != ValueType::from(&global.value) {
+                        violations.push(location.violation(
+                            key,
+                            Rule::GlobalType,
+                            String::from("attribute differs from type of global attribute"),
+                        ));
+                    }
+                }
+                None => violations.push(location.violation(
+                    key,
+                    Rule::MissingGlobal,
+                    String::from("global attribute is missing"),
+                )),
+            }
+        }
+    }
+
+    fn globals<'a>(&'a self, scope: &'a Scope) -> impl Iterator<Item = &'a Attribute> {
+        self.meta
+            .globals
+            .iter()
+            .filter(move |g| g.scope() == scope)
+            .flat_map(|g| g.attributes.iter())
+    }
+}
+
+fn is_nested(attribute: &Attribute) -> bool {
+    match &attribute.value {
+        AttributeType::List(values) if values.iter().any(is_nested) => true,
+        _ => !attribute.attributes.is_empty(),
+    }
+}
+
+impl<T: Stream> Stream for XesValidator<T> {
+    fn next(&mut self) -> ResOpt {
+        match self.stream.next()? {
+            Some(element) => {
+                self.validate_element(&element)?;
+                Ok(Some(element))
+            }
+            None => {
+                if self.declares_nested() && !self.nested {
+                    let location = Location {
+                        element: StreamState::Header,
+                        trace: None,
+                        event: None,
+                    };
+                    let violation = location.violation(
+                        None,
+                        Rule::UnusedNesting,
+                        String::from("feature \"nested-attributes\" is declared but not used"),
+                    );
+
+                    // report once only, even if polled repeatedly
+                    self.nested = true;
+                    self.handle(vec![violation])?;
+                }
+
+                Ok(None)
+            }
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -685,9 +1658,7 @@ mod tests {
     use crate::util::{expand_static, open_buffered};
     use std::fs;
     use std::io;
-    use std::io::Write;
     use std::path::PathBuf;
-    use std::process::{Command, Output, Stdio};
 
     fn deserialize_dir(path: PathBuf, expect_failure: bool) {
         for p in fs::read_dir(path).unwrap().map(|p| p.unwrap()) {
@@ -698,16 +1669,15 @@ mod tests {
             if expect_failure {
                 assert!(
                     result.is_err(),
-                    format!("parsing {:?} is expected to fail but didn't", p.path())
+                    "parsing {:?} is expected to fail but didn't",
+                    p.path()
                 );
             } else {
                 assert!(
                     result.is_ok(),
-                    format!(
-                        "parsing {:?} unexpectedly failed: {:?}",
-                        p.path(),
-                        result.err()
-                    )
+                    "parsing {:?} unexpectedly failed: {:?}",
+                    p.path(),
+                    result.err()
                 );
             }
         }
@@ -738,22 +1708,299 @@ mod tests {
         deserialize_dir(expand_static(&["xes", "non_validating"]), false);
     }
 
-    fn validate_xes(xes: &[u8]) -> Output {
-        let mut child = Command::new("xmllint")
-            .arg("--noout")
-            .arg("--schema")
-            .arg(expand_static(&["xes", "xes-ieee-1849-2016.xsd"]))
-            .arg("-")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("xmllint installed?");
+    fn validate_dir(path: PathBuf, expect_failure: bool) {
+        for p in fs::read_dir(path).unwrap().map(|p| p.unwrap()) {
+            let f = open_buffered(&p.path());
+            let mut validator = XesValidator::new(XesReader::from(f));
+            let result = consume(&mut validator);
 
-        child.stdin.as_mut().unwrap().write_all(xes).unwrap();
-        child.wait_with_output().unwrap()
+            if expect_failure {
+                assert!(
+                    result.is_err(),
+                    "validating {:?} is expected to fail but didn't",
+                    p.path()
+                );
+            } else {
+                assert!(
+                    result.is_ok(),
+                    "validating {:?} unexpectedly failed: {:?}",
+                    p.path(),
+                    result.err()
+                );
+            }
+        }
+    }
+
+    // Validate files that comply with the standard.
+    #[test]
+    fn test_validate_correct() {
+        validate_dir(expand_static(&["xes", "correct"]), false);
     }
 
-    fn validate_dir(path: PathBuf) {
+    // Validate files that parse successfully but don't comply with the standard, expecting failure.
+    #[test]
+    fn test_validate_non_validating() {
+        validate_dir(expand_static(&["xes", "non_validating"]), true);
+    }
+
+    #[test]
+    fn test_validate_list() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <trace>
+                    <list key="list">
+                        <values key="values">
+                            <string key="name" value="name1"/>
+                            <int key="name" value="2"/>
+                        </values>
+                    </list>
+                </trace>
+            </log>"#;
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader);
+        consume(&mut validator).unwrap();
+        assert!(validator.violations().is_empty());
+
+        // the schema requires exactly one `<values>` with a key
+        let malformed = [
+            s.replace(r#"<values key="values">"#, "<values>"),
+            s.replace("</values>", r#"</values><values key="more"/>"#),
+            s.replace(r#"<values key="values">"#, "<!--")
+                .replace("</values>", "-->"),
+        ];
+        for s in malformed.iter() {
+            let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+            let mut validator = XesValidator::reporting(reader);
+            consume(&mut validator).unwrap();
+
+            let rules: Vec<Rule> = validator.release().into_iter().map(|v| v.rule).collect();
+            assert_eq!(rules, vec![Rule::ListValues], "{}", s);
+        }
+
+        // written lists comply
+        let mut buffer = Buffer::default();
+        buffer
+            .consume(&mut XesReader::from(io::BufReader::new(s.as_bytes())))
+            .unwrap();
+        let mut writer = XesWriter::new(Vec::new(), None, None);
+        writer.consume(&mut buffer).unwrap();
+        let written = writer.into_inner();
+        let mut validator = XesValidator::reporting(XesReader::from(written.as_slice()));
+        consume(&mut validator).unwrap();
+        assert!(validator.violations().is_empty());
+
+        // list values with attributes are nested
+        let s = s.replace(
+            r#"<int key="name" value="2"/>"#,
+            r#"<int key="name" value="2"><string key="unit" value="pcs"/></int>"#,
+        );
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader);
+        consume(&mut validator).unwrap();
+
+        let rules: Vec<Rule> = validator.release().into_iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec![Rule::UndeclaredNesting]);
+
+        let s = s.replace(r#"xes.features="""#, r#"xes.features="nested-attributes""#);
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader);
+        consume(&mut validator).unwrap();
+        assert!(validator.violations().is_empty());
+    }
+
+    #[test]
+    fn test_validate_report() {
+        let param = [
+            ("correct", "event_correct_attributes.xes", vec![]),
+            (
+                "non_validating",
+                "event_identical_attributes.xes",
+                vec![
+                    (
+                        StreamState::Global,
+                        None,
+                        None,
+                        "instance",
+                        Rule::DuplicateKey,
+                    ),
+                    (
+                        StreamState::Event,
+                        Some(0),
+                        Some(0),
+                        "concept:name",
+                        Rule::DuplicateKey,
+                    ),
+                ],
+            ),
+            (
+                "non_validating",
+                "event_incorrect_type.xes",
+                vec![
+                    (
+                        StreamState::Event,
+                        Some(0),
+                        Some(1),
+                        "org:resource",
+                        Rule::ExtensionType,
+                    ),
+                    (
+                        StreamState::Event,
+                        Some(0),
+                        Some(1),
+                        "org:resource",
+                        Rule::GlobalType,
+                    ),
+                ],
+            ),
+            (
+                "non_validating",
+                "misplaced_classifier_trace.xes",
+                vec![
+                    (
+                        StreamState::Trace,
+                        Some(0),
+                        None,
+                        "concept:name",
+                        Rule::MissingGlobal,
+                    ),
+                    (StreamState::Classifier, None, None, "", Rule::Order),
+                    (
+                        StreamState::Classifier,
+                        None,
+                        None,
+                        "concept:name",
+                        Rule::ClassifierKey,
+                    ),
+                ],
+            ),
+        ];
+
+        for (d, f, expected) in param.iter() {
+            let path = expand_static(&["xes", d, f]);
+            let mut reference = Buffer::default();
+            let mut buffer = Buffer::default();
+
+            reference
+                .consume(&mut XesReader::from(open_buffered(&path)))
+                .unwrap();
+
+            let mut validator = XesValidator::reporting(XesReader::from(open_buffered(&path)));
+            buffer.consume(&mut validator).unwrap();
+
+            // the stream is forwarded completely
+            assert_eq!(buffer.len(), reference.len());
+
+            let violations = validator.release();
+            let violations: Vec<_> = violations
+                .iter()
+                .map(|v| {
+                    let key = v.key.as_deref().unwrap_or("");
+                    (v.element.clone(), v.trace, v.event, key, v.rule.clone())
+                })
+                .collect();
+
+            assert_eq!(&violations, expected, "unexpected violations in {:?}", f);
+            assert!(validator.violations().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_validate_extension_types() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <extension name="Time" prefix="t" uri="http://www.xes-standard.org/time.xesext"/>
+                <trace>
+                    <event>
+                        <string key="t:timestamp" value="yesterday"/>
+                    </event>
+                    <event>
+                        <date key="t:timestamp" value="2010-12-30T11:02:00.000+01:00"/>
+                    </event>
+                </trace>
+            </log>"#;
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader);
+
+        consume(&mut validator).unwrap();
+
+        let violations = validator.release();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::ExtensionType);
+        assert_eq!(violations[0].trace, Some(0));
+        assert_eq!(violations[0].event, Some(0));
+        assert_eq!(violations[0].key.as_deref(), Some("t:timestamp"));
+    }
+
+    #[test]
+    fn test_validate_user_extension() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <extension name="Ticket" prefix="ticket"
+                    uri="http://www.example.com/ticket.xesext"/>
+                <trace>
+                    <event>
+                        <int key="ticket:priority" value="1"/>
+                        <string key="ticket:escalated" value="yes"/>
+                    </event>
+                </trace>
+            </log>"#;
+
+        let mut registry = Registry::default();
+        registry
+            .load(&expand_static(&["xes", "xesext", "ticket.xesext"]))
+            .unwrap();
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader).with_registry(registry);
+
+        consume(&mut validator).unwrap();
+
+        let violations = validator.release();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::ExtensionType);
+        assert_eq!(violations[0].key.as_deref(), Some("ticket:escalated"));
+
+        // definitions are loaded from local URIs, types depend on the scope
+        let path = expand_static(&["xes", "xesext", "ticket.xesext"]);
+        let s = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <extension name="Ticket" prefix="tkt" uri="file://{}"/>
+                <trace>
+                    <string key="tkt:priority" value="high"/>
+                    <event>
+                        <string key="tkt:priority" value="high"/>
+                    </event>
+                </trace>
+            </log>"#,
+            path.to_str().unwrap()
+        );
+
+        // local definitions are only loaded on request
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader);
+        consume(&mut validator).unwrap();
+        assert!(validator.violations().is_empty());
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader).with_local_extensions();
+
+        consume(&mut validator).unwrap();
+
+        let violations = validator.release();
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, Rule::ExtensionType);
+        assert_eq!(violations[0].event, Some(0));
+        assert_eq!(violations[0].key.as_deref(), Some("tkt:priority"));
+    }
+
+    fn serialize_validate_dir(path: PathBuf) {
         for p in fs::read_dir(path).unwrap().map(|p| p.unwrap()) {
             let f = open_buffered(&p.path());
             let mut buffer = Buffer::default();
@@ -765,23 +2012,24 @@ mod tests {
             let mut writer = XesWriter::new(bytes, None, None);
             writer.consume(&mut buffer).unwrap();
 
-            let validation_result = validate_xes(&writer.into_inner()[..]);
+            // deserialize and validate
+            let reader = XesReader::from(io::Cursor::new(writer.into_inner()));
+            let result = consume(&mut XesValidator::new(reader));
 
             assert!(
-                validation_result.status.success(),
-                format!("validation failed for {:?}, {:?}", p, validation_result)
+                result.is_ok(),
+                "validation failed for {:?}, {:?}",
+                p.path(),
+                result.err()
             );
-
-            break;
         }
     }
 
-    // Test whether serialization to XES XML representation yield syntactically correct results.
-    // This test requires `xmllint` to be available in path.
+    // Test whether serialization to XES XML representation yields valid results.
     #[test]
     fn test_serialize_syntax() {
-        validate_dir(expand_static(&["xes", "correct"]));
-        validate_dir(expand_static(&["xes", "recoverable"]));
+        serialize_validate_dir(expand_static(&["xes", "correct"]));
+        serialize_validate_dir(expand_static(&["xes", "recoverable"]));
     }
 
     fn serde_loop_dir(path: PathBuf) {
@@ -820,4 +2068,230 @@ mod tests {
         serde_loop_dir(expand_static(&["xes", "correct"]));
         serde_loop_dir(expand_static(&["xes", "recoverable"]));
     }
+
+    #[test]
+    fn test_detect_gzip() {
+        let path = expand_static(&["xes", "book"]);
+
+        for p in fs::read_dir(path).unwrap().map(|p| p.unwrap()) {
+            let plain = fs::read(p.path()).unwrap();
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            io::Write::write_all(&mut encoder, &plain).unwrap();
+            let compressed = encoder.finish().unwrap();
+
+            assert!(!Decompressed::detect(plain.as_slice()).unwrap().is_gzip());
+            assert!(Decompressed::detect(compressed.as_slice())
+                .unwrap()
+                .is_gzip());
+
+            // magic bytes split across reads
+            let trickle = io::BufReader::with_capacity(1, compressed.as_slice());
+            let mut decompressed = Vec::new();
+            let mut reader = Decompressed::detect(trickle).unwrap();
+            assert!(reader.is_gzip());
+            io::Read::read_to_end(&mut reader, &mut decompressed).unwrap();
+            assert_eq!(decompressed, plain);
+
+            let mut expected = Buffer::default();
+            let mut reader = XesReader::detect(io::Cursor::new(plain)).unwrap();
+            expected.consume(&mut reader).unwrap();
+
+            let mut buffer = Buffer::default();
+            let mut reader = XesReader::detect(io::Cursor::new(compressed)).unwrap();
+            buffer.consume(&mut reader).unwrap();
+
+            assert_eq!(
+                format!("{:?}", buffer),
+                format!("{:?}", expected),
+                "{:?}",
+                p.path()
+            );
+        }
+
+        assert!(XesReader::detect(io::Cursor::new(vec![0x1f, 0x8b, 0, 0]))
+            .and_then(|mut r| consume(&mut r))
+            .is_err());
+
+        // input shorter than the magic bytes
+        for input in [&b""[..], &b"<"[..], &[0x1f][..]].iter() {
+            let mut reader = Decompressed::detect(*input).unwrap();
+            let mut content = Vec::new();
+            io::Read::read_to_end(&mut reader, &mut content).unwrap();
+            assert!(!reader.is_gzip());
+            assert_eq!(content.as_slice(), *input);
+        }
+    }
+
+    #[test]
+    fn test_write_gzip() {
+        let path = expand_static(&["xes", "book"]);
+
+        for p in fs::read_dir(path).unwrap().map(|p| p.unwrap()) {
+            let mut log = Log::default();
+            log.consume(&mut XesReader::from(open_buffered(&p.path())))
+                .unwrap();
+
+            // some classifier names contain spaces, which is no valid `xs:NCName` for writing
+            log.classifiers = log
+                .classifiers
+                .iter()
+                .map(|c| {
+                    let name = c.name().replace(' ', "");
+                    crate::Classifier::new(name, c.declared_scope().cloned(), c.keys.clone())
+                })
+                .collect();
+
+            let mut writer = XesWriter::new(Vec::new(), None, None);
+            writer.consume(&mut Buffer::from(log.clone())).unwrap();
+            let plain = writer.into_inner();
+
+            let mut writer = XesWriter::gzip(Vec::new(), None, None);
+            writer.consume(&mut Buffer::from(log)).unwrap();
+            let compressed = writer.finish().unwrap();
+
+            assert!(compressed.starts_with(&[0x1f, 0x8b]), "{:?}", p.path());
+            assert!(compressed.len() < plain.len(), "{:?}", p.path());
+
+            let mut decompressed = Vec::new();
+            io::Read::read_to_end(
+                &mut MultiGzDecoder::new(io::Cursor::new(compressed.clone())),
+                &mut decompressed,
+            )
+            .unwrap();
+            assert_eq!(decompressed, plain, "{:?}", p.path());
+
+            let mut expected = Buffer::default();
+            expected
+                .consume(&mut XesReader::from(io::Cursor::new(plain)))
+                .unwrap();
+            let mut buffer = Buffer::default();
+            buffer
+                .consume(&mut XesReader::detect(io::Cursor::new(compressed)).unwrap())
+                .unwrap();
+
+            assert_eq!(
+                format!("{:?}", buffer),
+                format!("{:?}", expected),
+                "{:?}",
+                p.path()
+            );
+        }
+    }
+
+    #[test]
+    fn test_optional_scope() {
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <extension name="Concept" prefix="concept"
+                    uri="http://www.xes-standard.org/concept.xesext"/>
+                <global>
+                    <string key="concept:name" value="__INVALID__"/>
+                </global>
+                <global scope="trace">
+                    <string key="concept:name" value="__INVALID__"/>
+                </global>
+                <classifier name="Activity" keys="concept:name"/>
+                <classifier name="Case" scope="trace" keys="concept:name"/>
+            </log>"#;
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::new(reader);
+        let mut log = Log::default();
+        log.consume(&mut validator).unwrap();
+
+        assert_eq!(log.globals[0].declared_scope(), None);
+        assert_eq!(log.globals[0].scope(), &Scope::Event);
+        assert_eq!(log.classifiers[0].declared_scope(), None);
+        assert_eq!(log.classifiers[0].scope(), &Scope::Event);
+        assert_eq!(log.classifiers[1].declared_scope(), Some(&Scope::Trace));
+
+        let mut writer = XesWriter::new(Vec::new(), None, None);
+        writer.consume(&mut Buffer::from(log)).unwrap();
+        let written = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert!(written.contains("<global>\n"));
+        assert!(written.contains(r#"<global scope="trace">"#));
+        assert!(written.contains(r#"<classifier name="Activity" keys="concept:name"/>"#));
+        assert!(written.contains(r#"<classifier name="Case" scope="trace" keys="concept:name"/>"#));
+
+        // classifiers without scope refer to event globals
+        let s = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <log xes.version="1849.2016" xes.features="">
+                <global scope="trace">
+                    <string key="case" value="__INVALID__"/>
+                </global>
+                <classifier name="Case" keys="case"/>
+            </log>"#;
+
+        let reader = XesReader::from(io::BufReader::new(s.as_bytes()));
+        let mut validator = XesValidator::reporting(reader);
+        consume(&mut validator).unwrap();
+
+        let rules: Vec<Rule> = validator.release().into_iter().map(|v| v.rule).collect();
+        assert_eq!(rules, vec![Rule::ClassifierKey]);
+    }
+
+    #[test]
+    fn test_error_position() {
+        let read =
+            |s: &str| consume(&mut XesReader::from(io::BufReader::new(s.as_bytes()))).unwrap_err();
+
+        // invalid value in the second event of the second trace
+        let s = "<log>
+<trace>
+    <event/>
+</trace>
+<trace>
+    <event><int key=\"a\" value=\"1\"/></event>
+    <event>
+        <int key=\"b\" value=\"x\"/>
+    </event>
+</trace>
+</log>";
+        let error = read(s);
+        let position = error.position().unwrap();
+        assert_eq!((position.line, position.column), (8, 9));
+        assert_eq!(position.offset, s.find("<int key=\"b\"").unwrap());
+        assert_eq!((position.trace, position.event), (Some(1), Some(1)));
+        assert!(format!("{}", error).ends_with(" at line 8, column 9, trace 1, event 1"));
+
+        // invalid scope outside of traces and events
+        let s = "<log>\n  <global scope=\"case\"/>\n</log>";
+        let position = read(s).position().cloned().unwrap();
+        assert_eq!((position.line, position.column), (2, 3));
+        assert_eq!((position.trace, position.event), (None, None));
+
+        // events outside of traces are counted separately
+        let s = concat!(
+            "<log>\n<trace><event/></trace>\n<event/>\n",
+            "<event><date key=\"t\" value=\"x\"/></event>\n</log>"
+        );
+        let position = read(s).position().cloned().unwrap();
+        assert_eq!((position.line, position.column), (4, 8));
+        assert_eq!((position.trace, position.event), (None, Some(1)));
+
+        // syntax errors are located at the name of the mismatched end tag
+        let s = "<log>\n<trace>\n</event>\n</log>";
+        let error = read(s);
+        let position = error.position().unwrap();
+        assert_eq!((position.line, position.column), (3, 3));
+        assert_eq!(position.offset, s.find("event>").unwrap());
+        assert_eq!((position.trace, position.event), (Some(0), None));
+        assert!(matches!(error.inner(), Error::XesError(_)));
+
+        let s = concat!(
+            "<log>\n<trace>\n  <event>\n    <string key=\"a\" value=\"b\">\n",
+            "  </event>\n</trace>\n</log>"
+        );
+        let error = read(s);
+        let position = error.position().unwrap();
+        assert_eq!((position.line, position.column), (5, 5));
+        assert_eq!((position.trace, position.event), (Some(0), Some(0)));
+        assert!(matches!(error.inner(), Error::XesError(_)));
+
+        // positions are tracked across reads of partial lines
+        let reader = io::BufReader::with_capacity(3, s.as_bytes());
+        let error = consume(&mut XesReader::from(reader)).unwrap_err();
+        assert_eq!(error.position().map(|p| (p.line, p.column)), Some((5, 5)));
+    }
 }
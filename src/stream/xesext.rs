@@ -1,10 +1,806 @@
 //! XES extensions
 //!
+//! An extension attaches semantics to a set of attributes by declaring their keys, types and the
+//! components (scopes) they may occur in. This module provides a registry of such extension
+//! definitions that ships with the standard extensions listed at
+//! [xes-standard.org](http://www.xes-standard.org/).
+//!
+//! Extensions are looked up by the `uri` or `prefix` found on `Extension` elements of a stream.
+//! Since the prefix used in a log may differ from the one suggested by the extension, attribute
+//! keys are resolved with respect to the extensions declared by the log.
+//!
+//! User-defined extensions can be loaded from `.xesext` files as specified by the
+//! [xesext schema](http://www.xes-standard.org/downloads/xesext-ieee-1849-2016.xsd). Extensions
+//! declared by a stream are loaded from their URI if it refers to a local file.
+//!
 
 // standard library
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
 // third party
+use quick_xml::events::{BytesStart as QxBytesStart, Event as QxEvent};
+use quick_xml::Reader as QxReader;
 
 // local
+use crate::error::{Error, Result};
+use crate::stream::xml_util::{validate_name, validate_ncname, validate_uri};
+use crate::{AttributeType, Extension, Scope};
+
+/// Type of an attribute's value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueType {
+    String,
+    Date,
+    Int,
+    Float,
+    Boolean,
+    Id,
+    List,
+}
+
+impl ValueType {
+    fn from_tag(tag: &[u8]) -> Option<Self> {
+        match tag {
+            b"string" => Some(ValueType::String),
+            b"date" => Some(ValueType::Date),
+            b"int" => Some(ValueType::Int),
+            b"float" => Some(ValueType::Float),
+            b"boolean" => Some(ValueType::Boolean),
+            b"id" => Some(ValueType::Id),
+            b"list" => Some(ValueType::List),
+            _ => None,
+        }
+    }
+}
+
+impl From<&AttributeType> for ValueType {
+    fn from(value: &AttributeType) -> Self {
+        match value {
+            AttributeType::String(_) => ValueType::String,
+            AttributeType::Date(_) => ValueType::Date,
+            AttributeType::Int(_) => ValueType::Int,
+            AttributeType::Float(_) => ValueType::Float,
+            AttributeType::Boolean(_) => ValueType::Boolean,
+            AttributeType::Id(_) => ValueType::Id,
+            AttributeType::List(_) => ValueType::List,
+        }
+    }
+}
+
+/// Component an extension attribute may be attached to
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionScope {
+    Log,
+    Trace,
+    Event,
+    Meta,
+}
+
+impl From<&Scope> for ExtensionScope {
+    fn from(scope: &Scope) -> Self {
+        match scope {
+            Scope::Trace => ExtensionScope::Trace,
+            Scope::Event => ExtensionScope::Event,
+        }
+    }
+}
+
+impl ExtensionScope {
+    fn from_tag(tag: &[u8]) -> Option<Self> {
+        match tag {
+            b"log" => Some(ExtensionScope::Log),
+            b"trace" => Some(ExtensionScope::Trace),
+            b"event" => Some(ExtensionScope::Event),
+            b"meta" => Some(ExtensionScope::Meta),
+            _ => None,
+        }
+    }
+}
+
+/// Attribute declared by an extension
+///
+/// The key is given without the extension's prefix, e.g. `name` for `concept:name`. A key may be
+/// declared several times with different types, given that their scopes differ.
+///
+#[derive(Debug, Clone)]
+pub struct ExtensionAttribute {
+    pub key: String,
+    pub value_type: ValueType,
+    pub scopes: Vec<ExtensionScope>,
+}
+
+/// Definition of a XES extension
+#[derive(Debug, Clone)]
+pub struct XesExtension {
+    pub name: String,
+    pub prefix: String,
+    pub uri: String,
+    pub attributes: Vec<ExtensionAttribute>,
+}
+
+impl XesExtension {
+    /// Look up a declared attribute by its scope and key without prefix
+    pub fn attribute(&self, scope: &ExtensionScope, key: &str) -> Option<&ExtensionAttribute> {
+        self.attributes
+            .iter()
+            .find(|a| a.key == key && a.scopes.contains(scope))
+    }
+
+    /// Parse an extension definition from `.xesext` XML
+    ///
+    /// Attributes declared in several scopes with the same type are merged into a single
+    /// definition. Aliases are not retained.
+    ///
+    pub fn read<R: io::BufRead>(reader: R) -> Result<Self> {
+        let mut reader = QxReader::from_reader(reader);
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut extension: Option<XesExtension> = None;
+        let mut scope: Option<ExtensionScope> = None;
+        let mut depth: usize = 0;
+
+        loop {
+            let event = reader.read_event(&mut buffer).map_err(|e| {
+                Error::XesError(format!(
+                    "Error at position {}: {:?}",
+                    reader.buffer_position(),
+                    e
+                ))
+            })?;
+
+            match event {
+                QxEvent::Start(ref tag) | QxEvent::Empty(ref tag) => {
+                    let empty = matches!(event, QxEvent::Empty(_));
+
+                    match (depth, &mut extension, ExtensionScope::from_tag(tag.name())) {
+                        (0, None, _) if tag.name() == b"xesextension" => {
+                            let attributes = tag_attributes(tag)?;
+                            extension = Some(XesExtension {
+                                name: validate_ncname(get_attr(&attributes, "name")?)?.to_string(),
+                                prefix: validate_ncname(get_attr(&attributes, "prefix")?)?
+                                    .to_string(),
+                                uri: validate_uri(get_attr(&attributes, "uri")?)?.to_string(),
+                                attributes: Vec::new(),
+                            });
+                        }
+                        (1, Some(_), Some(s)) => scope = Some(s),
+                        (2, Some(extension), _) if scope.is_some() => {
+                            let value_type = ValueType::from_tag(tag.name()).ok_or_else(|| {
+                                Error::XesError(format!(
+                                    "unexpected xesext element: {:?}",
+                                    String::from_utf8_lossy(tag.name())
+                                ))
+                            })?;
+                            let attributes = tag_attributes(tag)?;
+                            let key = validate_name(get_attr(&attributes, "key")?)?;
+
+                            extension.add_attribute(key, value_type, scope.clone().unwrap());
+                        }
+                        (3, Some(_), _) if tag.name() == b"alias" => (),
+                        _ => {
+                            return Err(Error::XesError(format!(
+                                "unexpected xesext element: {:?}",
+                                String::from_utf8_lossy(tag.name())
+                            )))
+                        }
+                    }
+
+                    if !empty {
+                        depth += 1;
+                    }
+                }
+                QxEvent::End(_) => {
+                    depth -= 1;
+
+                    if depth == 1 {
+                        scope = None;
+                    }
+                }
+                QxEvent::Eof => break,
+                _ => (),
+            }
+
+            buffer.clear();
+        }
+
+        extension.ok_or_else(|| Error::XesError(String::from("missing xesextension element")))
+    }
+
+    fn add_attribute(&mut self, key: &str, value_type: ValueType, scope: ExtensionScope) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|a| a.key == key && a.value_type == value_type)
+        {
+            Some(attribute) => {
+                if !attribute.scopes.contains(&scope) {
+                    attribute.scopes.push(scope)
+                }
+            }
+            None => self.attributes.push(ExtensionAttribute {
+                key: key.to_string(),
+                value_type,
+                scopes: vec![scope],
+            }),
+        }
+    }
+}
+
+fn tag_attributes(tag: &QxBytesStart) -> Result<HashMap<String, String>> {
+    let mut attributes = HashMap::new();
+
+    for attribute in tag.attributes() {
+        let attribute = attribute?;
+        attributes.insert(
+            String::from_utf8(attribute.key.to_vec())?,
+            String::from_utf8(attribute.value.to_vec())?,
+        );
+    }
+
+    Ok(attributes)
+}
+
+fn get_attr<'a>(attributes: &'a HashMap<String, String>, key: &str) -> Result<&'a str> {
+    match attributes.get(key) {
+        Some(value) => Ok(value),
+        None => Err(Error::KeyError(format!(
+            "missing {:?} attribute in xesext",
+            key
+        ))),
+    }
+}
+
+// Standard extension definitions: name, prefix, uri and attributes (key, type, scopes)
+type Definition = (
+    &'static str,
+    &'static str,
+    &'static str,
+    &'static [(&'static str, ValueType, &'static [ExtensionScope])],
+);
+
+const LOG: &[ExtensionScope] = &[ExtensionScope::Log];
+const EVENT: &[ExtensionScope] = &[ExtensionScope::Event];
+const META: &[ExtensionScope] = &[ExtensionScope::Meta];
+const TRACE_EVENT: &[ExtensionScope] = &[ExtensionScope::Trace, ExtensionScope::Event];
+const LOG_TRACE_EVENT: &[ExtensionScope] = &[
+    ExtensionScope::Log,
+    ExtensionScope::Trace,
+    ExtensionScope::Event,
+];
+const ALL: &[ExtensionScope] = &[
+    ExtensionScope::Log,
+    ExtensionScope::Trace,
+    ExtensionScope::Event,
+    ExtensionScope::Meta,
+];
+
+const STANDARD_EXTENSIONS: &[Definition] = &[
+    (
+        "Concept",
+        "concept",
+        "http://www.xes-standard.org/concept.xesext",
+        &[
+            ("name", ValueType::String, LOG_TRACE_EVENT),
+            ("instance", ValueType::String, EVENT),
+        ],
+    ),
+    (
+        "Time",
+        "time",
+        "http://www.xes-standard.org/time.xesext",
+        &[("timestamp", ValueType::Date, EVENT)],
+    ),
+    (
+        "Lifecycle",
+        "lifecycle",
+        "http://www.xes-standard.org/lifecycle.xesext",
+        &[
+            ("model", ValueType::String, LOG),
+            ("transition", ValueType::String, EVENT),
+        ],
+    ),
+    (
+        "Organizational",
+        "org",
+        "http://www.xes-standard.org/org.xesext",
+        &[
+            ("resource", ValueType::String, EVENT),
+            ("role", ValueType::String, EVENT),
+            ("group", ValueType::String, EVENT),
+        ],
+    ),
+    (
+        "Semantic",
+        "semantic",
+        "http://www.xes-standard.org/semantic.xesext",
+        &[("modelReference", ValueType::String, ALL)],
+    ),
+    (
+        "Cost",
+        "cost",
+        "http://www.xes-standard.org/cost.xesext",
+        &[
+            ("total", ValueType::Float, TRACE_EVENT),
+            ("currency", ValueType::String, TRACE_EVENT),
+            ("drivers", ValueType::List, TRACE_EVENT),
+            ("amount", ValueType::Float, META),
+            ("driver", ValueType::String, META),
+            ("type", ValueType::String, META),
+        ],
+    ),
+    (
+        "Identity",
+        "identity",
+        "http://www.xes-standard.org/identity.xesext",
+        &[("id", ValueType::Id, ALL)],
+    ),
+    (
+        "Micro",
+        "micro",
+        "http://www.xes-standard.org/micro.xesext",
+        &[
+            ("level", ValueType::Int, EVENT),
+            ("parentId", ValueType::Id, EVENT),
+            ("length", ValueType::Int, EVENT),
+        ],
+    ),
+    (
+        "Software Communication",
+        "swcomm",
+        "http://www.xes-standard.org/swcomm.xesext",
+        &[
+            ("localHost", ValueType::String, EVENT),
+            ("localIP", ValueType::String, EVENT),
+            ("localPort", ValueType::Int, EVENT),
+            ("remoteHost", ValueType::String, EVENT),
+            ("remoteIP", ValueType::String, EVENT),
+            ("remotePort", ValueType::Int, EVENT),
+        ],
+    ),
+    (
+        "Software Event",
+        "swevent",
+        "http://www.xes-standard.org/swevent.xesext",
+        &[
+            ("type", ValueType::String, EVENT),
+            ("callee-package", ValueType::String, EVENT),
+            ("callee-class", ValueType::String, EVENT),
+            ("callee-method", ValueType::String, EVENT),
+            ("callee-paramSig", ValueType::String, EVENT),
+            ("callee-returnSig", ValueType::String, EVENT),
+            ("callee-isConstructor", ValueType::Boolean, EVENT),
+            ("callee-instanceId", ValueType::String, EVENT),
+            ("callee-filename", ValueType::String, EVENT),
+            ("callee-lineNr", ValueType::Int, EVENT),
+            ("caller-package", ValueType::String, EVENT),
+            ("caller-class", ValueType::String, EVENT),
+            ("caller-method", ValueType::String, EVENT),
+            ("caller-paramSig", ValueType::String, EVENT),
+            ("caller-returnSig", ValueType::String, EVENT),
+            ("caller-isConstructor", ValueType::Boolean, EVENT),
+            ("caller-instanceId", ValueType::String, EVENT),
+            ("caller-filename", ValueType::String, EVENT),
+            ("caller-lineNr", ValueType::Int, EVENT),
+            ("hasData", ValueType::Boolean, EVENT),
+            ("hasException", ValueType::Boolean, EVENT),
+            ("returnValue", ValueType::String, EVENT),
+            ("params", ValueType::List, EVENT),
+            ("valueType", ValueType::String, EVENT),
+            ("exThrown", ValueType::String, EVENT),
+            ("exCaught", ValueType::String, EVENT),
+            ("threadId", ValueType::String, EVENT),
+            ("nanotime", ValueType::Int, EVENT),
+            ("appName", ValueType::String, EVENT),
+            ("appTier", ValueType::String, EVENT),
+            ("appNode", ValueType::String, EVENT),
+            ("appSession", ValueType::String, EVENT),
+        ],
+    ),
+    (
+        "Software Telemetry",
+        "swtelemetry",
+        "http://www.xes-standard.org/swtelemetry.xesext",
+        &[
+            ("cpuTotalUser", ValueType::Float, EVENT),
+            ("cpuTotalKernel", ValueType::Float, EVENT),
+            ("cpuTotalIdle", ValueType::Float, EVENT),
+            ("cpuLoadUser", ValueType::Float, EVENT),
+            ("cpuLoadKernel", ValueType::Float, EVENT),
+            ("cpuLoadIdle", ValueType::Float, EVENT),
+            ("threadTotal", ValueType::Int, EVENT),
+            ("threadDaemon", ValueType::Int, EVENT),
+            ("memoryUsed", ValueType::Int, EVENT),
+            ("memoryTotal", ValueType::Int, EVENT),
+            ("memoryLoad", ValueType::Float, EVENT),
+            ("jvmMemoryUsed", ValueType::Int, EVENT),
+            ("jvmMemoryTotal", ValueType::Int, EVENT),
+            ("jvmMemoryLoad", ValueType::Float, EVENT),
+        ],
+    ),
+];
+
+lazy_static! {
+    /// Registry of the standard extensions
+    pub(crate) static ref STANDARD: Registry = Registry::default();
+}
+
+/// Registry of extension definitions
+///
+/// The default registry contains all standard extensions, i.e. Concept, Time, Lifecycle,
+/// Organizational, Semantic, Cost, Identity, Micro and the Software extensions. Further extensions
+/// can be registered. Definitions registered later take precedence over earlier ones.
+///
+#[derive(Debug, Clone)]
+pub struct Registry {
+    extensions: Vec<XesExtension>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let extensions = STANDARD_EXTENSIONS
+            .iter()
+            .map(|(name, prefix, uri, attributes)| XesExtension {
+                name: name.to_string(),
+                prefix: prefix.to_string(),
+                uri: uri.to_string(),
+                attributes: attributes
+                    .iter()
+                    .map(|(key, value_type, scopes)| ExtensionAttribute {
+                        key: key.to_string(),
+                        value_type: value_type.clone(),
+                        scopes: scopes.to_vec(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Registry { extensions }
+    }
+}
+
+impl Registry {
+    /// Create a registry without any extension definitions
+    pub fn empty() -> Self {
+        Registry {
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Register an extension definition
+    pub fn register(&mut self, extension: XesExtension) {
+        self.extensions.push(extension);
+    }
+
+    /// Load an extension definition from a `.xesext` file and register it
+    pub fn load(&mut self, path: &Path) -> Result<&XesExtension> {
+        let extension = XesExtension::read(io::BufReader::new(fs::File::open(path)?))?;
+        self.register(extension);
+
+        Ok(self.extensions.last().unwrap())
+    }
+
+    /// Load the definition of a declared extension from its URI, unless it's registered already
+    ///
+    /// Only `file` URIs that refer to regular files of at most 1 MiB are supported, definitions
+    /// behind other URIs aren't fetched. The loaded definition is registered under the declared
+    /// URI. Returns `None` if no definition is found.
+    ///
+    /// As the URI stems from the log, only use this for trusted input.
+    ///
+    pub fn load_declared(&mut self, extension: &Extension) -> Result<Option<&XesExtension>> {
+        if self.lookup(extension).is_none() {
+            if let Some(path) = file_path(&extension.uri) {
+                // don't block on or exhaust special files, such as FIFOs or devices
+                let metadata = fs::metadata(&path)?;
+                if !metadata.is_file() || metadata.len() > MAX_DEFINITION_SIZE {
+                    return Err(Error::FormatError(format!(
+                        "{:?} is no regular file of at most {} bytes",
+                        path, MAX_DEFINITION_SIZE
+                    )));
+                }
+
+                let file = io::Read::take(fs::File::open(path)?, MAX_DEFINITION_SIZE);
+                let mut definition = XesExtension::read(io::BufReader::new(file))?;
+                definition.uri = extension.uri.clone();
+                self.register(definition);
+            }
+        }
+
+        Ok(self.lookup(extension))
+    }
+
+    /// All registered extension definitions
+    pub fn extensions(&self) -> &[XesExtension] {
+        &self.extensions
+    }
+
+    /// Look up an extension definition by prefix
+    pub fn by_prefix(&self, prefix: &str) -> Option<&XesExtension> {
+        self.extensions.iter().rev().find(|e| e.prefix == prefix)
+    }
+
+    /// Look up an extension definition by URI
+    pub fn by_uri(&self, uri: &str) -> Option<&XesExtension> {
+        self.extensions.iter().rev().find(|e| e.uri == uri)
+    }
+
+    /// Look up the definition of an extension element, preferring its URI over its prefix
+    pub fn lookup(&self, extension: &Extension) -> Option<&XesExtension> {
+        self.by_uri(&extension.uri)
+            .or_else(|| self.by_prefix(&extension.prefix))
+    }
+
+    /// Resolve a prefixed attribute key of the given scope with respect to the declared extensions
+    ///
+    /// Returns `None` if the key has no prefix, the prefix is not declared or the extension doesn't
+    /// define such an attribute for that scope.
+    ///
+    pub fn resolve(
+        &self,
+        scope: &ExtensionScope,
+        key: &str,
+        declared: &[Extension],
+    ) -> Option<&ExtensionAttribute> {
+        let i = key.find(':')?;
+        let (prefix, key) = (&key[..i], &key[i + 1..]);
+        let extension = declared.iter().find(|e| e.prefix == prefix)?;
+
+        self.lookup(extension)?.attribute(scope, key)
+    }
+}
+
+/// Maximum size of extension definitions loaded from declared URIs
+const MAX_DEFINITION_SIZE: u64 = 1 << 20;
+
+fn file_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let path = path.strip_prefix("localhost").unwrap_or(path);
+
+    Some(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::expand_static;
+
+    fn extension(name: &str, prefix: &str, uri: &str) -> Extension {
+        Extension {
+            name: name.to_string(),
+            prefix: prefix.to_string(),
+            uri: uri.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_standard_extensions() {
+        let registry = Registry::default();
+        let prefixes = [
+            "concept",
+            "time",
+            "lifecycle",
+            "org",
+            "semantic",
+            "cost",
+            "identity",
+            "micro",
+            "swcomm",
+            "swevent",
+            "swtelemetry",
+        ];
+
+        assert_eq!(registry.extensions().len(), prefixes.len());
+
+        for prefix in prefixes.iter() {
+            let by_prefix = registry.by_prefix(prefix).unwrap();
+            let by_uri = registry.by_uri(&by_prefix.uri).unwrap();
+
+            assert_eq!(by_prefix.name, by_uri.name);
+        }
+
+        assert!(Registry::empty().by_prefix("concept").is_none());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let registry = Registry::default();
+        let declared = [
+            extension("Time", "time", "http://www.xes-standard.org/time.xesext"),
+            extension("Concept", "c", "http://www.xes-standard.org/concept.xesext"),
+            extension(
+                "Lifecycle",
+                "lifecycle",
+                "http://example.com/lifecycle.xesext",
+            ),
+        ];
+
+        let timestamp = registry
+            .resolve(&ExtensionScope::Event, "time:timestamp", &declared)
+            .unwrap();
+        assert_eq!(timestamp.value_type, ValueType::Date);
+        assert_eq!(timestamp.scopes, vec![ExtensionScope::Event]);
+
+        // prefix differs from the suggested one
+        let name = registry
+            .resolve(&ExtensionScope::Event, "c:name", &declared)
+            .unwrap();
+        assert_eq!(name.value_type, ValueType::String);
+        assert!(registry
+            .resolve(&ExtensionScope::Event, "concept:name", &declared)
+            .is_none());
+
+        // URI differs, prefix serves as fallback
+        let transition = registry
+            .resolve(&ExtensionScope::Event, "lifecycle:transition", &declared)
+            .unwrap();
+        assert_eq!(transition.value_type, ValueType::String);
+
+        assert!(registry
+            .resolve(&ExtensionScope::Event, "time:fnord", &declared)
+            .is_none());
+        assert!(registry
+            .resolve(&ExtensionScope::Event, "org:resource", &declared)
+            .is_none());
+        assert!(registry
+            .resolve(&ExtensionScope::Event, "timestamp", &declared)
+            .is_none());
+        assert!(registry
+            .resolve(&ExtensionScope::Trace, "time:timestamp", &declared)
+            .is_none());
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = Registry::default();
+
+        registry.register(XesExtension {
+            name: String::from("Time"),
+            prefix: String::from("time"),
+            uri: String::from("http://www.xes-standard.org/time.xesext"),
+            attributes: vec![ExtensionAttribute {
+                key: String::from("timestamp"),
+                value_type: ValueType::String,
+                scopes: vec![ExtensionScope::Event],
+            }],
+        });
+
+        let declared = [extension(
+            "Time",
+            "time",
+            "http://www.xes-standard.org/time.xesext",
+        )];
+        let timestamp = registry
+            .resolve(&ExtensionScope::Event, "time:timestamp", &declared)
+            .unwrap();
+
+        assert_eq!(timestamp.value_type, ValueType::String);
+    }
+
+    #[test]
+    fn test_load() {
+        let mut registry = Registry::default();
+        let path = expand_static(&["xes", "xesext", "ticket.xesext"]);
+        let ticket = registry.load(&path).unwrap();
+
+        assert_eq!(ticket.name, "Ticket");
+        assert_eq!(ticket.prefix, "ticket");
+        assert_eq!(ticket.uri, "http://www.example.com/ticket.xesext");
+        assert_eq!(ticket.attributes.len(), 5);
+
+        let opened = ticket.attribute(&ExtensionScope::Trace, "opened").unwrap();
+        assert_eq!(opened.value_type, ValueType::Date);
+        assert_eq!(
+            opened.scopes,
+            vec![ExtensionScope::Trace, ExtensionScope::Event]
+        );
+
+        let declared = [extension(
+            "Ticket",
+            "tkt",
+            "http://www.example.com/ticket.xesext",
+        )];
+        let priority = registry
+            .resolve(&ExtensionScope::Event, "tkt:priority", &declared)
+            .unwrap();
+        assert_eq!(priority.value_type, ValueType::Int);
+        assert_eq!(priority.scopes, vec![ExtensionScope::Event]);
+        assert!(registry
+            .resolve(&ExtensionScope::Trace, "tkt:id", &declared)
+            .is_some());
+        assert!(registry
+            .resolve(&ExtensionScope::Event, "tkt:id", &declared)
+            .is_none());
+
+        let path = expand_static(&["xes", "xesext", "missing.xesext"]);
+        assert!(registry.load(&path).is_err());
+    }
+
+    #[test]
+    fn test_scoped_types() {
+        let xml = r#"<xesextension name="A" prefix="a" uri="http://a.org">
+                <trace><int key="status"/><date key="opened"/></trace>
+                <event><string key="status"/><date key="opened"/></event>
+            </xesextension>"#;
+        let extension = XesExtension::read(xml.as_bytes()).unwrap();
+
+        let status = extension
+            .attribute(&ExtensionScope::Trace, "status")
+            .unwrap();
+        assert_eq!(status.value_type, ValueType::Int);
+
+        let status = extension
+            .attribute(&ExtensionScope::Event, "status")
+            .unwrap();
+        assert_eq!(status.value_type, ValueType::String);
+
+        assert!(extension
+            .attribute(&ExtensionScope::Log, "status")
+            .is_none());
+        assert_eq!(extension.attributes.len(), 3);
+    }
+
+    #[test]
+    fn test_load_declared() {
+        let mut registry = Registry::default();
+        let path = expand_static(&["xes", "xesext", "ticket.xesext"]);
+        let uri = format!("file://{}", path.to_str().unwrap());
+        let declared = [extension("Ticket", "tkt", &uri)];
+
+        let ticket = registry.load_declared(&declared[0]).unwrap().unwrap();
+        assert_eq!(ticket.name, "Ticket");
+        assert_eq!(ticket.uri, uri);
+
+        let priority = registry.resolve(&ExtensionScope::Event, "tkt:priority", &declared);
+        assert_eq!(priority.unwrap().value_type, ValueType::Int);
+
+        // registered already
+        let count = registry.extensions().len();
+        registry.load_declared(&declared[0]).unwrap();
+        assert_eq!(registry.extensions().len(), count);
+
+        // remote definitions aren't fetched
+        let remote = extension("Ticket", "tkt", "http://www.example.com/ticket.xesext");
+        assert!(registry.load_declared(&remote).unwrap().is_none());
+
+        let missing = extension("Missing", "m", "file:///promi/missing.xesext");
+        assert!(registry.load_declared(&missing).is_err());
+
+        // special files are refused
+        let device = extension("Zero", "z", "file:///dev/zero");
+        assert!(matches!(
+            registry.load_declared(&device),
+            Err(Error::FormatError(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let invalid = [
+            r#"<xesextension prefix="a" uri="http://a.org"/>"#,
+            r#"<xesextension name="A" prefix="a:b" uri="http://a.org"/>"#,
+            concat!(
+                r#"<xesextension name="A" prefix="a" uri="http://a.org">"#,
+                r#"<event><foo key="x"/></event></xesextension>"#
+            ),
+            concat!(
+                r#"<xesextension name="A" prefix="a" uri="http://a.org">"#,
+                r#"<string key="x"/></xesextension>"#
+            ),
+            r#"<extension name="A" prefix="a" uri="http://a.org"/>"#,
+            "",
+        ];
+
+        for xml in invalid.iter() {
+            assert!(XesExtension::read(xml.as_bytes()).is_err(), "{}", xml);
+        }
 
-pub struct XesExtension {/* TODO to be implemented */}
+        let empty = r#"<xesextension name="A" prefix="a" uri="http://a.org"><log/></xesextension>"#;
+        assert!(XesExtension::read(empty.as_bytes())
+            .unwrap()
+            .attributes
+            .is_empty());
+    }
+}
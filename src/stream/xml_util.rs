@@ -10,6 +10,9 @@
 //! * `xs:Name`
 //! * `xs:NCName`
 //! * `xs:anyURI`
+//! * `xs:decimal`
+//!
+//! Besides, UUIDs as required by XES `id` attributes can be validated.
 //!
 
 // standard library
@@ -35,6 +38,13 @@ const RE_NAME_START_CHAR: &str = r#"(:|[A-Z]|_|[a-z]|[\x{C0}-\x{D6}]|[\x{D8}-\x{
 // https://www.w3.org/2011/04/XMLSchema/TypeLibrary-URI-RFC3986.xsd (Simple type URI-3986)
 const RE_URI: &str = r#"(([A-Za-z])[A-Za-z0-9+\-\.]*):((//(((([A-Za-z0-9\-\._~!$&'()*+,;=:]|(%[0-9A-Fa-f][0-9A-Fa-f]))*@))?((\[(((((([0-9A-Fa-f]){0,4}:)){6}((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|(::((([0-9A-Fa-f]){0,4}:)){5}((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|((([0-9A-Fa-f]){0,4})?::((([0-9A-Fa-f]){0,4}:)){4}((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|(((((([0-9A-Fa-f]){0,4}:))?([0-9A-Fa-f]){0,4}))?::((([0-9A-Fa-f]){0,4}:)){3}((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|(((((([0-9A-Fa-f]){0,4}:)){0,2}([0-9A-Fa-f]){0,4}))?::((([0-9A-Fa-f]){0,4}:)){2}((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|(((((([0-9A-Fa-f]){0,4}:)){0,3}([0-9A-Fa-f]){0,4}))?::([0-9A-Fa-f]){0,4}:((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|(((((([0-9A-Fa-f]){0,4}:)){0,4}([0-9A-Fa-f]){0,4}))?::((([0-9A-Fa-f]){0,4}:([0-9A-Fa-f]){0,4})|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))))|(((((([0-9A-Fa-f]){0,4}:)){0,5}([0-9A-Fa-f]){0,4}))?::([0-9A-Fa-f]){0,4})|(((((([0-9A-Fa-f]){0,4}:)){0,6}([0-9A-Fa-f]){0,4}))?::))|(v([0-9A-Fa-f])+\.(([A-Za-z0-9\-\._~]|[!$&'()*+,;=]|:))+))\])|(([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5]))\.([0-9]|([1-9][0-9])|(1([0-9]){2})|(2[0-4][0-9])|(25[0-5])))|(([A-Za-z0-9\-\._~]|(%[0-9A-Fa-f][0-9A-Fa-f])|[!$&'()*+,;=]))*)((:([0-9])*))?)((/(([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f])))*))*)|(/(((([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f])))+((/(([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f])))*))*))?)|((([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f])))+((/(([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f])))*))*))((\?((([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f]))|/|\?))*))?((#((([A-Za-z0-9\-\._~!$&'()*+,;=:@]|(%[0-9A-Fa-f][0-9A-Fa-f]))|/|\?))*))?"#;
 
+// `xs:decimal`, see https://www.w3.org/TR/xmlschema-2/#decimal
+const RE_DECIMAL: &str = r"^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)$";
+
+// UUIDs in their canonical textual representation, see https://tools.ietf.org/html/rfc4122
+const RE_UUID: &str =
+    r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$";
+
 lazy_static! {
     static ref RE_LETTER: String = format!("({}|{})", RE_BASE_CHAR, RE_IDEOGRAPHIC);
 
@@ -61,20 +71,26 @@ lazy_static! {
     static ref CRE_COMBINING_CHAR: Regex = Regex::new(RE_COMBINING_CHAR).unwrap();
     static ref CRE_DIGIT: Regex = Regex::new(RE_DIGIT).unwrap();
     static ref CRE_EXTENDER: Regex = Regex::new(RE_EXTENDER).unwrap();
-    static ref CRE_LETTER: Regex = Regex::new(&*RE_LETTER).unwrap();
+    static ref CRE_LETTER: Regex = Regex::new(&RE_LETTER).unwrap();
 
     // string level
     /// Compiled regular expression for validating `xs:Token`
-    pub static ref CRE_TOKEN: Regex = Regex::new(&*RE_TOKEN).unwrap();
+    pub static ref CRE_TOKEN: Regex = Regex::new(&RE_TOKEN).unwrap();
 
     /// Compiled regular expression for validating `xs:Name`
-    pub static ref CRE_NAME: Regex = Regex::new(&*RE_NAME).unwrap();
+    pub static ref CRE_NAME: Regex = Regex::new(&RE_NAME).unwrap();
 
     /// Compiled regular expression for validating `xs:NCName`
-    pub static ref CRE_NCNAME: Regex = Regex::new(&*RE_NCNAME).unwrap();
+    pub static ref CRE_NCNAME: Regex = Regex::new(&RE_NCNAME).unwrap();
 
     /// Compiled regular expression for validating `xs:anyUri`
     pub static ref CRE_URI: Regex = Regex::new(RE_URI).unwrap();
+
+    /// Compiled regular expression for validating `xs:decimal`
+    pub static ref CRE_DECIMAL: Regex = Regex::new(RE_DECIMAL).unwrap();
+
+    /// Compiled regular expression for validating UUIDs
+    pub static ref CRE_UUID: Regex = Regex::new(RE_UUID).unwrap();
 }
 
 /// Parse string as boolean
@@ -96,7 +112,7 @@ pub fn parse_bool(string: &str) -> Result<bool> {
 /// For further information, see: [www.w3.org](https://www.w3.org/TR/xmlschema-2/#token)
 ///
 pub fn validate_token(token: &str) -> Result<&str> {
-    if (&*CRE_TOKEN).is_match(token) {
+    if CRE_TOKEN.is_match(token) {
         Ok(token)
     } else {
         Err(Error::ValidationError(format!(
@@ -111,7 +127,7 @@ pub fn validate_token(token: &str) -> Result<&str> {
 /// For further information, see: [www.w3.org](https://www.w3.org/TR/xmlschema-2/#Name)
 ///
 pub fn validate_name(name: &str) -> Result<&str> {
-    if (&*CRE_NAME).is_match(name) {
+    if CRE_NAME.is_match(name) {
         Ok(name)
     } else {
         Err(Error::ValidationError(format!(
@@ -126,7 +142,7 @@ pub fn validate_name(name: &str) -> Result<&str> {
 /// For further information, see: [www.w3.org](https://www.w3.org/TR/xmlschema-2/#NCName)
 ///
 pub fn validate_ncname(ncname: &str) -> Result<&str> {
-    if (&*CRE_NCNAME).is_match(ncname) {
+    if CRE_NCNAME.is_match(ncname) {
         Ok(ncname)
     } else {
         Err(Error::ValidationError(format!(
@@ -141,7 +157,7 @@ pub fn validate_ncname(ncname: &str) -> Result<&str> {
 /// For further information, see: [www.w3.org](https://www.w3.org/TR/xmlschema-2/#anyURI)
 ///
 pub fn validate_uri(uri: &str) -> Result<&str> {
-    if (&*CRE_URI).is_match(uri) {
+    if CRE_URI.is_match(uri) {
         Ok(uri)
     } else {
         Err(Error::ValidationError(format!(
@@ -151,6 +167,37 @@ pub fn validate_uri(uri: &str) -> Result<&str> {
     }
 }
 
+/// Validate `xs:decimal` string type
+///
+/// For further information, see: [www.w3.org](https://www.w3.org/TR/xmlschema-2/#decimal)
+///
+pub fn validate_decimal(decimal: &str) -> Result<&str> {
+    if CRE_DECIMAL.is_match(decimal) {
+        Ok(decimal)
+    } else {
+        Err(Error::ValidationError(format!(
+            "{:?} is no valid `xs:decimal`",
+            decimal
+        )))
+    }
+}
+
+/// Validate UUID string
+///
+/// XES requires values of `id` attributes to be UUIDs, see
+/// [RFC 4122](https://tools.ietf.org/html/rfc4122)
+///
+pub fn validate_uuid(uuid: &str) -> Result<&str> {
+    if CRE_UUID.is_match(uuid) {
+        Ok(uuid)
+    } else {
+        Err(Error::ValidationError(format!(
+            "{:?} is no valid UUID",
+            uuid
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,15 +214,15 @@ mod tests {
 
     fn assert_matches(regex: &Regex, matches: &[&str], no_matches: &[&str]) {
         for (m, n) in matches.iter().zip(no_matches.iter()) {
-            assert!(regex.is_match(m), format!("\"{}\" must match", m));
-            assert!(!regex.is_match(n), format!("\"{}\" must not match", n));
+            assert!(regex.is_match(m), "\"{}\" must match", m);
+            assert!(!regex.is_match(n), "\"{}\" must not match", n);
         }
     }
 
     #[test]
     fn test_base_char() {
         assert_matches(
-            &*CRE_BASE_CHAR,
+            &CRE_BASE_CHAR,
             &["a", "B", "z", "ß", "ᇂ", "ἕ", "ῆ"],
             &["#", "*", "0", "<", "\"", "°", "="],
         );
@@ -184,7 +231,7 @@ mod tests {
     #[test]
     fn test_ideographic() {
         assert_matches(
-            &*CRE_IDEOGRAPHIC,
+            &CRE_IDEOGRAPHIC,
             &["〩", "龥", "一", "〡", "〇"],
             &["a", "*", "0", "<", "\"", "°", "ä"],
         );
@@ -193,7 +240,7 @@ mod tests {
     #[test]
     fn test_combining_char() {
         assert_matches(
-            &*CRE_COMBINING_CHAR,
+            &CRE_COMBINING_CHAR,
             &["ْ", "ੰ", "ெ", "ை"],
             &["a", ".", "0", "<"],
         );
@@ -202,7 +249,7 @@ mod tests {
     #[test]
     fn test_digit() {
         assert_matches(
-            &*CRE_DIGIT,
+            &CRE_DIGIT,
             &["0", "9", "۹", "༩", "०"],
             &["a", "*", ">", "<", "\"", "°", "ä"],
         )
@@ -211,7 +258,7 @@ mod tests {
     #[test]
     fn test_extender() {
         assert_matches(
-            &*CRE_EXTENDER,
+            &CRE_EXTENDER,
             &["·", "ー", "ـ", "ゝ", "々", "ໆ"],
             &["a", "*", "0", "<", "\"", "°", "ä"],
         );
@@ -220,7 +267,7 @@ mod tests {
     #[test]
     fn test_letter() {
         assert_matches(
-            &*CRE_LETTER,
+            &CRE_LETTER,
             &[
                 "a", "B", "z", "ß", "ᇂ", "ἕ", "ῆ", "〩", "龥", "一", "〡", "〇",
             ],
@@ -231,7 +278,7 @@ mod tests {
     #[test]
     fn test_token() {
         assert_matches(
-            &*CRE_TOKEN,
+            &CRE_TOKEN,
             &["foo bar", "fnord", "BAZ42"],
             &["foo  bar", " fnord", "ba 32 "],
         );
@@ -240,7 +287,7 @@ mod tests {
     #[test]
     fn test_name() {
         assert_matches(
-            &*CRE_NAME,
+            &CRE_NAME,
             &["fo:o", "Bar-·⁀ͯ", "øͰͽBAZ", "fnord42"],
             &["-foo", "foo bar", "5BAZ", ""],
         );
@@ -249,7 +296,7 @@ mod tests {
     #[test]
     fn test_ncname() {
         assert_matches(
-            &*CRE_NCNAME,
+            &CRE_NCNAME,
             &["foo", "Bar", "BAZ", "fnord42"],
             &[":foo", "foo bar", "5BAZ", ""],
         );
@@ -258,7 +305,7 @@ mod tests {
     #[test]
     fn test_uri() {
         assert_matches(
-            &*CRE_URI,
+            &CRE_URI,
             &[
                 "https://john.doe@www.example.com:123/forum/questions/?tag=net&order=newest#top",
                 "ldap://[2001:db8::7]/c=GB?objectClass?one",
@@ -268,4 +315,25 @@ mod tests {
             &[" ", "foo bar", "5BAZ", ""],
         );
     }
+
+    #[test]
+    fn test_decimal() {
+        assert_matches(
+            &CRE_DECIMAL,
+            &["1849.2016", "1.0", "-42", ".5"],
+            &["1849.2016.1", "1,0", "", "a"],
+        );
+    }
+
+    #[test]
+    fn test_uuid() {
+        assert_matches(
+            &CRE_UUID,
+            &[
+                "550e8400-e29b-41d4-a716-446655440000",
+                "6BA7B810-9DAD-11D1-80B4-00C04FD430C8",
+            ],
+            &["No ID", "550e8400e29b41d4a716446655440000"],
+        );
+    }
 }